@@ -0,0 +1,58 @@
+use lettre::{
+    AsyncSmtpTransport, AsyncTransport, Tokio1Executor,
+    transport::smtp::{authentication::Credentials, response::Response},
+};
+
+use crate::{
+    EmailCredentials, FormattedMessage, SendEmailError, SendPolicy, async_smtp_transport_builder,
+};
+
+/// Delivers messages to an SMTP server using lettre's async (tokio) SMTP
+/// transport, the async counterpart of [`crate::SmtpMailTransport`], see
+/// [`crate::send_email_with_async_transport`].
+///
+/// Messages are checked against the [`SendPolicy`] of the server before
+/// they are sent, see [`SendPolicy::for_smtp_server`].
+#[derive(Clone)]
+pub struct AsyncSmtpMailTransport {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    policy: SendPolicy,
+}
+
+impl AsyncSmtpMailTransport {
+    /// Replaces the policy derived from the SMTP server.
+    pub fn with_send_policy(self, policy: SendPolicy) -> Self {
+        Self { policy, ..self }
+    }
+
+    /// The checks done before a message is composed and sent with this transport.
+    pub fn send_policy(&self) -> &SendPolicy {
+        &self.policy
+    }
+
+    /// Delivers the `message`, returning the response of the SMTP server.
+    ///
+    /// Must be awaited from within a tokio runtime.
+    pub async fn send_message(
+        &self,
+        message: &FormattedMessage,
+    ) -> Result<Response, SendEmailError> {
+        self.transport
+            .send_raw(message.envelope(), &message.encode()?)
+            .await
+            .map_err(SendEmailError::SendEmail)
+    }
+}
+
+impl TryFrom<EmailCredentials> for AsyncSmtpMailTransport {
+    type Error = SendEmailError;
+
+    fn try_from(credentials: EmailCredentials) -> Result<Self, Self::Error> {
+        let policy = SendPolicy::for_smtp_server(credentials.smtp_server());
+        let transport = async_smtp_transport_builder(credentials.smtp_server())
+            .map_err(SendEmailError::CreateSmtpTransport)?
+            .credentials(Credentials::from(credentials))
+            .build();
+        Ok(Self { transport, policy })
+    }
+}
@@ -1,8 +1,13 @@
+use std::{io::Read, path::Path};
+
 use bon::Builder;
 use getset::Getters;
 use serde::{Deserialize, Serialize};
 
-/// A binary attachment that can be added to an email.
+use crate::{AttachmentData, AttachmentError};
+
+/// A binary attachment that can be added to an email, its content is shared
+/// by clones or read from a file when sent, see [`AttachmentData`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Builder, Getters, Serialize, Deserialize)]
 pub struct Attachment {
     #[getset(get = "pub")]
@@ -11,24 +16,147 @@ pub struct Attachment {
     #[getset(get = "pub")]
     mime_type: String,
 
+    #[builder(into)]
+    #[getset(get = "pub")]
+    data: AttachmentData,
+
+    /// Content-ID of an inline attachment, referenced from an HTML body as `cid:<content_id>`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     #[getset(get = "pub")]
-    data: Vec<u8>,
+    content_id: Option<String>,
 }
 
 impl Attachment {
     pub fn new(
         name: impl Into<String>,
         mime_type: impl Into<String>,
-        data: impl Into<Vec<u8>>,
+        data: impl Into<AttachmentData>,
     ) -> Self {
         Self {
             name: name.into(),
             mime_type: mime_type.into(),
             data: data.into(),
+            content_id: None,
+        }
+    }
+
+    /// An attachment displayed inline in the HTML body, which references it
+    /// by its `content_id`, e.g. `<img src="cid:logo">`.
+    pub fn inline(
+        content_id: impl Into<String>,
+        name: impl Into<String>,
+        mime_type: impl Into<String>,
+        data: impl Into<AttachmentData>,
+    ) -> Self {
+        Self {
+            content_id: Some(content_id.into()),
+            ..Self::new(name, mime_type, data)
+        }
+    }
+
+    /// Whether this attachment is displayed inline, referenced by Content-ID.
+    pub fn is_inline(&self) -> bool {
+        self.content_id.is_some()
+    }
+
+    /// MIME type used when neither the content nor the file name reveal the type.
+    pub const FALLBACK_MIME_TYPE: &str = "application/octet-stream";
+
+    /// File name used when sanitizing leaves nothing of the original name.
+    pub const FALLBACK_NAME: &str = "attachment";
+
+    /// Count of leading bytes used to infer the MIME type of a file.
+    const SNIFF_LEN: u64 = 8192;
+
+    /// An attachment backed by the file at `path`, named after the file, see
+    /// [`Attachment::sanitize_file_name`], with the MIME type inferred by
+    /// [`Attachment::sniff_mime_type`].
+    ///
+    /// Only the first bytes are read now, to infer the MIME type, the content
+    /// is read when the email is sent, see [`AttachmentData::File`].
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, AttachmentError> {
+        let path = path.as_ref();
+        let name = path
+            .file_name()
+            .ok_or_else(|| AttachmentError::NoFileName {
+                path: path.to_owned(),
+            })?
+            .to_string_lossy();
+        let mut head = Vec::new();
+        std::fs::File::open(path)
+            .and_then(|file| file.take(Self::SNIFF_LEN).read_to_end(&mut head))
+            .map_err(|underlying| AttachmentError::ReadFile {
+                path: path.to_owned(),
+                underlying,
+            })?;
+
+        let name = Self::sanitize_file_name(&name);
+        let mime_type = Self::sniff_mime_type(&name, &head);
+        Ok(Self::new(name, mime_type, AttachmentData::file(path)))
+    }
+
+    /// Reads `reader` to its end into an attachment named `name`, like
+    /// [`Attachment::from_path`] but held in memory.
+    pub fn from_reader(
+        name: impl AsRef<str>,
+        mut reader: impl Read,
+    ) -> Result<Self, AttachmentError> {
+        let mut data = Vec::new();
+        reader
+            .read_to_end(&mut data)
+            .map_err(|underlying| AttachmentError::ReadStream {
+                name: name.as_ref().to_owned(),
+                underlying,
+            })?;
+        let name = Self::sanitize_file_name(name.as_ref());
+        let mime_type = Self::sniff_mime_type(&name, &data);
+        Ok(Self::new(name, mime_type, data))
+    }
+
+    /// Infers the MIME type of `data` from its magic bytes, falling back to
+    /// the extension of `name` and then to [`Attachment::FALLBACK_MIME_TYPE`].
+    ///
+    /// The extension is preferred over magic bytes of generic containers, e.g.
+    /// a `.odt` file is a ZIP archive but sent as an OpenDocument text.
+    pub fn sniff_mime_type(name: &str, data: &[u8]) -> String {
+        let by_extension = mime_guess::from_path(name).first_raw();
+        match infer::get(data).map(|kind| kind.mime_type()) {
+            Some("application/zip") if by_extension.is_some() => by_extension,
+            Some(by_content) => Some(by_content),
+            None => by_extension,
+        }
+        .unwrap_or(Self::FALLBACK_MIME_TYPE)
+        .to_owned()
+    }
+
+    /// The last component of `name` without characters that are unsafe in file
+    /// names on common platforms, e.g. `../invoice:1.pdf` becomes `invoice_1.pdf`.
+    ///
+    /// Control characters and invisible formatting characters, such as the
+    /// right-to-left override U+202E which can disguise the extension of a
+    /// name, are removed. Reserved characters are replaced by `_`, and surrounding
+    /// whitespace and dots trimmed, so the name can neither traverse
+    /// directories nor be hidden: `.env` becomes `env`. Falls back to
+    /// [`Attachment::FALLBACK_NAME`] if nothing remains.
+    pub fn sanitize_file_name(name: &str) -> String {
+        let name = name.rsplit(['/', '\\']).next().unwrap_or_default();
+        let sanitized = name
+            .chars()
+            .filter(|c| !c.is_control() && !is_invisible_format_char(*c))
+            .map(|c| match c {
+                '<' | '>' | ':' | '"' | '|' | '?' | '*' => '_',
+                c => c,
+            })
+            .collect::<String>();
+        let sanitized = sanitized.trim_matches(|c: char| c.is_whitespace() || c == '.');
+        if sanitized.is_empty() {
+            Self::FALLBACK_NAME.to_owned()
+        } else {
+            sanitized.to_owned()
         }
     }
 
-    pub fn pdf(name: impl Into<String>, data: impl Into<Vec<u8>>) -> Self {
+    pub fn pdf(name: impl Into<String>, data: impl Into<AttachmentData>) -> Self {
         Self::new(name, "application/pdf", data)
     }
 
@@ -39,4 +167,104 @@ impl Attachment {
     pub fn sample_other() -> Self {
         Self::new("notes.txt", "text/plain", b"Hello from mejla".to_vec())
     }
+
+    pub fn sample_inline() -> Self {
+        Self::inline(
+            "logo",
+            "logo.png",
+            "image/png",
+            vec![0x89, 0x50, 0x4e, 0x47],
+        )
+    }
+}
+
+/// Whether `c` is an invisible formatting character, i.e. a bidirectional
+/// control, zero-width character, soft hyphen or byte order mark.
+fn is_invisible_format_char(c: char) -> bool {
+    matches!(
+        c,
+        '\u{00ad}'
+            | '\u{061c}'
+            | '\u{180e}'
+            | '\u{200b}'..='\u{200f}'
+            | '\u{202a}'..='\u{202e}'
+            | '\u{2060}'..='\u{2064}'
+            | '\u{2066}'..='\u{206f}'
+            | '\u{feff}'
+            | '\u{fff9}'..='\u{fffb}'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG: [u8; 8] = [0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a];
+
+    #[test]
+    fn sniff_mime_type() {
+        assert_eq!(Attachment::sniff_mime_type("logo.bin", &PNG), "image/png");
+        assert_eq!(Attachment::sniff_mime_type("logo.pdf", &PNG), "image/png");
+        assert_eq!(
+            Attachment::sniff_mime_type("invoices.csv", b"no,amount\n42,100"),
+            "text/csv"
+        );
+        assert_eq!(
+            Attachment::sniff_mime_type("letter.odt", b"PK\x03\x04"),
+            "application/vnd.oasis.opendocument.text"
+        );
+        assert_eq!(
+            Attachment::sniff_mime_type("data", b"\x00\x01"),
+            Attachment::FALLBACK_MIME_TYPE
+        );
+    }
+
+    #[test]
+    fn sanitize_file_name() {
+        assert_eq!(
+            Attachment::sanitize_file_name("../invoice:1.pdf"),
+            "invoice_1.pdf"
+        );
+        assert_eq!(
+            Attachment::sanitize_file_name("C:\\Users\\bob\\a\tb?.txt"),
+            "ab_.txt"
+        );
+        assert_eq!(Attachment::sanitize_file_name(" .env "), "env");
+        assert_eq!(
+            Attachment::sanitize_file_name("invoice\u{202e}fdp.exe"),
+            "invoicefdp.exe"
+        );
+        assert_eq!(
+            Attachment::sanitize_file_name("\u{feff}re\u{200b}port\u{2066}.pdf"),
+            "report.pdf"
+        );
+        assert_eq!(
+            Attachment::sanitize_file_name(".."),
+            Attachment::FALLBACK_NAME
+        );
+    }
+
+    #[test]
+    fn from_path() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("logo.png");
+        std::fs::write(&path, PNG).unwrap();
+        assert_eq!(
+            Attachment::from_path(&path).unwrap(),
+            Attachment::new("logo.png", "image/png", AttachmentData::file(&path))
+        );
+        assert!(matches!(
+            Attachment::from_path(directory.path().join("missing.pdf")),
+            Err(AttachmentError::ReadFile { .. })
+        ));
+    }
+
+    #[test]
+    fn from_reader() {
+        let attachment = Attachment::from_reader("notes/../notes.txt", &b"Hello"[..]).unwrap();
+        assert_eq!(
+            attachment,
+            Attachment::new("notes.txt", "text/plain", b"Hello".to_vec())
+        );
+    }
 }
@@ -0,0 +1,197 @@
+use std::{
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// The content of an [`crate::Attachment`], either shared bytes in memory or a
+/// file that is read when the email is sent.
+///
+/// Cloning is cheap in both cases, so emails with large attachments can be
+/// cloned, e.g. once per recipient of a [`crate::MailMerge`], without copying
+/// the content. The content is base64 encoded in chunks while a message is
+/// written, see [`crate::FormattedMessage`], so writing a message to a file
+/// never holds file-backed content in memory as a whole.
+///
+/// Equality and hashing compare bytes in memory by content but file-backed
+/// content by path, without reading the files. So the same file attached
+/// twice is equal, while a file and a copy of its bytes, or two files with
+/// the same content, are not.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AttachmentData {
+    /// Bytes in memory, shared by all clones.
+    Bytes(Arc<[u8]>),
+
+    /// A file read when the email is sent, so it must still exist then.
+    File { path: PathBuf },
+}
+
+/// Count of bytes encoded into one line of base64 content, which makes
+/// lines of 76 characters.
+const BASE64_LINE_BYTES: u64 = 57;
+
+/// Count of bytes read and encoded at a time, a whole number of lines.
+const BASE64_CHUNK_BYTES: u64 = BASE64_LINE_BYTES * 1024;
+
+impl AttachmentData {
+    /// Content backed by the file at `path`, which is not read until the
+    /// email is sent.
+    pub fn file(path: impl Into<PathBuf>) -> Self {
+        Self::File { path: path.into() }
+    }
+
+    /// The bytes if the content is in memory.
+    pub fn bytes(&self) -> Option<&[u8]> {
+        match self {
+            Self::Bytes(bytes) => Some(bytes),
+            Self::File { .. } => None,
+        }
+    }
+
+    /// The path of the file if the content is file-backed.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::Bytes(_) => None,
+            Self::File { path } => Some(path),
+        }
+    }
+
+    /// Opens the content for reading, which fails if the file of
+    /// file-backed content cannot be opened.
+    pub fn open(&self) -> std::io::Result<Box<dyn Read + '_>> {
+        Ok(match self {
+            Self::Bytes(bytes) => Box::new(&bytes[..]),
+            Self::File { path } => Box::new(std::fs::File::open(path)?),
+        })
+    }
+
+    /// The size of the content in bytes, which for file-backed content is
+    /// the current size of the file.
+    pub fn size(&self) -> std::io::Result<u64> {
+        match self {
+            Self::Bytes(bytes) => Ok(bytes.len() as u64),
+            Self::File { path } => std::fs::metadata(path).map(|metadata| metadata.len()),
+        }
+    }
+
+    /// The size in bytes of `size` bytes of content once base64 encoded in
+    /// lines of 76 characters separated by CRLF.
+    pub(crate) fn base64_size(size: u64) -> u64 {
+        if size == 0 {
+            return 0;
+        }
+        let lines = size.div_ceil(BASE64_LINE_BYTES);
+        4 * size.div_ceil(3) + 2 * (lines - 1)
+    }
+
+    /// Writes the content base64 encoded in lines of 76 characters separated
+    /// by CRLF, as lettre encodes bodies, reading and encoding it in chunks.
+    pub(crate) fn write_base64(&self, writer: &mut impl Write) -> std::io::Result<()> {
+        let mut reader = self.open()?;
+        let mut chunk = Vec::with_capacity(BASE64_CHUNK_BYTES as usize);
+        let mut encoded = String::new();
+        let mut first = true;
+        loop {
+            chunk.clear();
+            (&mut reader)
+                .take(BASE64_CHUNK_BYTES)
+                .read_to_end(&mut chunk)?;
+            if chunk.is_empty() {
+                return Ok(());
+            }
+            if !first {
+                writer.write_all(b"\r\n")?;
+            }
+            first = false;
+            encoded.clear();
+            email_encoding::body::base64::encode(&chunk, &mut encoded)
+                .expect("writing to a String never fails");
+            writer.write_all(encoded.as_bytes())?;
+        }
+    }
+}
+
+impl From<Arc<[u8]>> for AttachmentData {
+    fn from(bytes: Arc<[u8]>) -> Self {
+        Self::Bytes(bytes)
+    }
+}
+
+impl From<Vec<u8>> for AttachmentData {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self::Bytes(bytes.into())
+    }
+}
+
+impl From<&[u8]> for AttachmentData {
+    fn from(bytes: &[u8]) -> Self {
+        Self::Bytes(bytes.into())
+    }
+}
+
+impl<const N: usize> From<[u8; N]> for AttachmentData {
+    fn from(bytes: [u8; N]) -> Self {
+        Self::Bytes(bytes.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clones_share_bytes() {
+        let data = AttachmentData::from(vec![1, 2, 3]);
+        let clone = data.clone();
+        assert!(std::ptr::eq(
+            data.bytes().unwrap().as_ptr(),
+            clone.bytes().unwrap().as_ptr()
+        ));
+    }
+
+    #[test]
+    fn file_content_is_compared_by_path() {
+        assert_eq!(
+            AttachmentData::file("report.pdf"),
+            AttachmentData::file("report.pdf")
+        );
+        assert_ne!(
+            AttachmentData::file("report.pdf"),
+            AttachmentData::file("copy.pdf")
+        );
+        assert_eq!(
+            AttachmentData::from([1, 2]),
+            AttachmentData::from(vec![1, 2])
+        );
+    }
+
+    #[test]
+    fn base64_size_is_exact() {
+        let chunk = BASE64_CHUNK_BYTES as usize;
+        for size in [0, 1, 2, 3, 56, 57, 58, 114, 115, chunk, chunk * 2 + 100] {
+            let data = AttachmentData::from(vec![0xff; size]);
+            let mut encoded = Vec::new();
+            data.write_base64(&mut encoded).unwrap();
+            assert_eq!(
+                encoded,
+                lettre::message::Body::new(vec![0xff; size]).as_ref(),
+                "{size} bytes"
+            );
+            assert_eq!(
+                AttachmentData::base64_size(size as u64),
+                encoded.len() as u64
+            );
+        }
+    }
+
+    #[test]
+    fn serde_is_backwards_compatible() {
+        let data: AttachmentData = serde_json::from_str("[1,2,3]").unwrap();
+        assert_eq!(data, AttachmentData::from([1, 2, 3]));
+        let file: AttachmentData = serde_json::from_str(r#"{"path":"report.pdf"}"#).unwrap();
+        assert_eq!(file, AttachmentData::file("report.pdf"));
+    }
+}
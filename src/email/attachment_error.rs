@@ -0,0 +1,54 @@
+use std::{
+    fmt::{Display, Formatter},
+    path::PathBuf,
+};
+
+/// Errors that can occur while creating an [`crate::Attachment`] from a file or reader.
+#[derive(Debug)]
+pub enum AttachmentError {
+    /// `path` does not end with a file name, e.g. `..` or `/`.
+    NoFileName { path: PathBuf },
+
+    /// The file at `path` could not be read.
+    ReadFile {
+        path: PathBuf,
+        underlying: std::io::Error,
+    },
+
+    /// The reader of the attachment named `name` failed.
+    ReadStream {
+        name: String,
+        underlying: std::io::Error,
+    },
+}
+
+impl Display for AttachmentError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoFileName { path } => {
+                write!(f, "attachment path {} has no file name", path.display())
+            }
+            Self::ReadFile { path, underlying } => {
+                write!(
+                    f,
+                    "failed to read attachment {}: {underlying}",
+                    path.display()
+                )
+            }
+            Self::ReadStream { name, underlying } => {
+                write!(f, "failed to read attachment {name}: {underlying}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AttachmentError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::NoFileName { .. } => None,
+            Self::ReadFile { underlying, .. } | Self::ReadStream { underlying, .. } => {
+                Some(underlying)
+            }
+        }
+    }
+}
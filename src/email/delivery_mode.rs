@@ -0,0 +1,26 @@
+use std::path::PathBuf;
+
+/// How [`crate::send_email_with_delivery_mode`] delivers an email.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum DeliveryMode {
+    /// Sends the email to the SMTP server.
+    #[default]
+    Smtp,
+
+    /// Writes the email as an `.eml` file into `directory` instead of sending
+    /// it, e.g. to preview it in a mail client, see [`crate::FileMailTransport`].
+    DryRun { directory: PathBuf },
+}
+
+impl DeliveryMode {
+    /// A dry run writing into `directory`, which must already exist.
+    pub fn dry_run(directory: impl Into<PathBuf>) -> Self {
+        Self::DryRun {
+            directory: directory.into(),
+        }
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        matches!(self, Self::DryRun { .. })
+    }
+}
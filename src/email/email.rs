@@ -1,4 +1,4 @@
-use crate::{Attachment, EmailAccount, EmailAddress};
+use crate::{Attachment, EmailAccount, EmailBody, Recipient};
 use bon::Builder;
 use getset::Getters;
 use indexmap::IndexSet;
@@ -8,40 +8,62 @@ use indexmap::IndexSet;
 pub struct Email {
     #[builder(default)]
     #[getset(get = "pub")]
-    public_recipients: IndexSet<EmailAddress>,
+    public_recipients: IndexSet<Recipient>,
 
     #[builder(default)]
     #[getset(get = "pub")]
-    cc_recipients: IndexSet<EmailAddress>,
+    cc_recipients: IndexSet<Recipient>,
 
     #[builder(default)]
     #[getset(get = "pub")]
-    bcc_recipients: IndexSet<EmailAddress>,
+    bcc_recipients: IndexSet<Recipient>,
 
     #[builder(default)]
     #[getset(get = "pub")]
     subject: String,
 
+    /// The plain-text body.
     body: Option<String>,
 
+    /// The HTML body, sent as `multipart/alternative` together with the plain-text body if both are set.
+    #[getset(get = "pub")]
+    html_body: Option<String>,
+
     #[getset(get = "pub")]
     reply_to: Option<EmailAccount>,
 
+    /// A set, so an attachment equal to one already in it is only sent once,
+    /// see [`crate::AttachmentData`] for how file-backed content is compared.
     #[builder(default)]
     #[getset(get = "pub")]
     attachments: IndexSet<Attachment>,
+
+    /// The `Message-ID` without angle brackets, e.g. `abc.123@example.com`, if
+    /// `None` one is generated when the email is sent.
+    #[getset(get = "pub")]
+    message_id: Option<String>,
 }
 
 impl Email {
+    /// The plain-text body, empty if there is none.
     pub fn body(&self) -> String {
         self.body.clone().unwrap_or_default()
     }
 
+    /// The plain-text and HTML bodies.
+    pub fn email_body(&self) -> EmailBody {
+        match (self.body.clone(), self.html_body.clone()) {
+            (Some(text), Some(html)) => EmailBody::alternative(text, html),
+            (None, Some(html)) => EmailBody::from_html(html),
+            (text, None) => EmailBody::from_text(text.unwrap_or_default()),
+        }
+    }
+
     pub fn sample() -> Self {
         Self::builder()
-            .public_recipients(IndexSet::from_iter(vec![EmailAddress::sample_bob()]))
-            .cc_recipients(IndexSet::from_iter(vec![EmailAddress::sample_carol()]))
-            .bcc_recipients(IndexSet::from_iter(vec![EmailAddress::sample_erin()]))
+            .public_recipients(IndexSet::from_iter(vec![Recipient::sample_bob()]))
+            .cc_recipients(IndexSet::from_iter(vec![Recipient::sample_carol()]))
+            .bcc_recipients(IndexSet::from_iter(vec![Recipient::sample_erin()]))
             .subject("Sample Email Subject".to_string())
             .body("This is a sample email body.".to_string())
             .attachments(IndexSet::from_iter(vec![Attachment::sample()]))
@@ -50,8 +72,8 @@ impl Email {
 
     pub fn sample_other() -> Self {
         Self::builder()
-            .public_recipients(IndexSet::from_iter(vec![EmailAddress::sample_alice()]))
-            .cc_recipients(IndexSet::from_iter(vec![EmailAddress::sample_dave()]))
+            .public_recipients(IndexSet::from_iter(vec![Recipient::sample_alice()]))
+            .cc_recipients(IndexSet::from_iter(vec![Recipient::sample_dave()]))
             .subject("Another Sample Email Subject".to_string())
             .body("This is another sample email body.".to_string())
             .attachments(IndexSet::from_iter(vec![Attachment::sample_other()]))
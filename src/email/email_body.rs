@@ -0,0 +1,54 @@
+use getset::Getters;
+
+/// The body of an email: plain text, HTML, or both, in which case it is sent
+/// as `multipart/alternative` with the plain text as fallback.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Getters)]
+pub struct EmailBody {
+    #[getset(get = "pub")]
+    text: Option<String>,
+
+    #[getset(get = "pub")]
+    html: Option<String>,
+}
+
+impl EmailBody {
+    /// A plain-text only body.
+    pub fn from_text(text: impl Into<String>) -> Self {
+        Self {
+            text: Some(text.into()),
+            html: None,
+        }
+    }
+
+    /// An HTML only body.
+    pub fn from_html(html: impl Into<String>) -> Self {
+        Self {
+            text: None,
+            html: Some(html.into()),
+        }
+    }
+
+    /// An HTML body with a plain-text fallback.
+    pub fn alternative(text: impl Into<String>, html: impl Into<String>) -> Self {
+        Self {
+            text: Some(text.into()),
+            html: Some(html.into()),
+        }
+    }
+
+    pub fn into_parts(self) -> (Option<String>, Option<String>) {
+        (self.text, self.html)
+    }
+}
+
+impl From<String> for EmailBody {
+    fn from(text: String) -> Self {
+        Self::from_text(text)
+    }
+}
+
+impl From<&str> for EmailBody {
+    fn from(text: &str) -> Self {
+        Self::from_text(text)
+    }
+}
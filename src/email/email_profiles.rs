@@ -0,0 +1,353 @@
+use std::fmt::{Display, Formatter};
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use crate::EncryptedEmailSettings;
+
+/// Named [`EncryptedEmailSettings`], e.g. one per sending mailbox such as
+/// `"billing"`, `"support"` and `"noreply"`, one of which is the default profile.
+///
+/// There is always at least one profile, and the default profile always exists.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "EmailProfilesRepr")]
+pub struct EmailProfiles {
+    default_profile: String,
+    profiles: IndexMap<String, EncryptedEmailSettings>,
+}
+
+/// Serialized form of [`EmailProfiles`], validated when deserializing.
+#[derive(Deserialize)]
+struct EmailProfilesRepr {
+    default_profile: String,
+    profiles: IndexMap<String, EncryptedEmailSettings>,
+}
+
+impl TryFrom<EmailProfilesRepr> for EmailProfiles {
+    type Error = EmailProfilesError;
+
+    fn try_from(repr: EmailProfilesRepr) -> Result<Self, Self::Error> {
+        if let Some(name) = repr.profiles.keys().find(|name| name.trim().is_empty()) {
+            return Err(EmailProfilesError::InvalidName { name: name.clone() });
+        }
+        if !repr.profiles.contains_key(&repr.default_profile) {
+            return Err(EmailProfilesError::NotFound {
+                name: repr.default_profile,
+            });
+        }
+        Ok(Self {
+            default_profile: repr.default_profile,
+            profiles: repr.profiles,
+        })
+    }
+}
+
+/// Errors that can occur when managing [`EmailProfiles`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EmailProfilesError {
+    /// There is no profile named `name`.
+    NotFound { name: String },
+
+    /// There already is a profile named `name`.
+    AlreadyExists { name: String },
+
+    /// Profile names must not be empty or only whitespace.
+    InvalidName { name: String },
+
+    /// The only profile cannot be removed.
+    CannotRemoveLastProfile,
+}
+
+impl Display for EmailProfilesError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound { name } => write!(f, "no email profile named '{name}'"),
+            Self::AlreadyExists { name } => {
+                write!(f, "an email profile named '{name}' already exists")
+            }
+            Self::InvalidName { name } => write!(f, "invalid email profile name '{name}'"),
+            Self::CannotRemoveLastProfile => {
+                write!(f, "cannot remove the last email profile")
+            }
+        }
+    }
+}
+
+impl std::error::Error for EmailProfilesError {}
+
+impl EmailProfiles {
+    /// Name of the profile created when migrating settings from before profiles existed.
+    pub const DEFAULT_PROFILE_NAME: &str = "default";
+
+    /// Creates profiles containing only `settings` named `name`, which is the default profile.
+    pub fn new(
+        name: impl Into<String>,
+        settings: EncryptedEmailSettings,
+    ) -> Result<Self, EmailProfilesError> {
+        let name = Self::validated_name(name.into())?;
+        Ok(Self {
+            default_profile: name.clone(),
+            profiles: IndexMap::from([(name, settings)]),
+        })
+    }
+
+    fn validated_name(name: String) -> Result<String, EmailProfilesError> {
+        if name.trim().is_empty() {
+            Err(EmailProfilesError::InvalidName { name })
+        } else {
+            Ok(name)
+        }
+    }
+
+    /// Name of the default profile.
+    pub fn default_profile_name(&self) -> &str {
+        &self.default_profile
+    }
+
+    /// Settings of the default profile.
+    pub fn default_profile(&self) -> &EncryptedEmailSettings {
+        self.profiles
+            .get(&self.default_profile)
+            .expect("default profile always exists")
+    }
+
+    /// Settings of the profile named `name`, if any.
+    pub fn get(&self, name: &str) -> Option<&EncryptedEmailSettings> {
+        self.profiles.get(name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.profiles.contains_key(name)
+    }
+
+    /// Names of all profiles, in insertion order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.profiles.keys().map(String::as_str)
+    }
+
+    /// All profiles with their names, in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &EncryptedEmailSettings)> {
+        self.profiles
+            .iter()
+            .map(|(name, settings)| (name.as_str(), settings))
+    }
+
+    pub fn len(&self) -> usize {
+        self.profiles.len()
+    }
+
+    /// Always `false`, since there is always at least one profile.
+    pub fn is_empty(&self) -> bool {
+        self.profiles.is_empty()
+    }
+
+    /// Adds a new profile, failing if there already is a profile named `name`.
+    pub fn add(
+        &mut self,
+        name: impl Into<String>,
+        settings: EncryptedEmailSettings,
+    ) -> Result<(), EmailProfilesError> {
+        let name = Self::validated_name(name.into())?;
+        if self.profiles.contains_key(&name) {
+            return Err(EmailProfilesError::AlreadyExists { name });
+        }
+        self.profiles.insert(name, settings);
+        Ok(())
+    }
+
+    /// Adds a profile or replaces the settings of an existing one, returning
+    /// the replaced settings.
+    pub fn insert(
+        &mut self,
+        name: impl Into<String>,
+        settings: EncryptedEmailSettings,
+    ) -> Result<Option<EncryptedEmailSettings>, EmailProfilesError> {
+        let name = Self::validated_name(name.into())?;
+        Ok(self.profiles.insert(name, settings))
+    }
+
+    /// Removes the profile named `name`, if it was the default profile the
+    /// first remaining profile becomes the default.
+    pub fn remove(&mut self, name: &str) -> Result<EncryptedEmailSettings, EmailProfilesError> {
+        if !self.profiles.contains_key(name) {
+            return Err(EmailProfilesError::NotFound {
+                name: name.to_owned(),
+            });
+        }
+        if self.profiles.len() == 1 {
+            return Err(EmailProfilesError::CannotRemoveLastProfile);
+        }
+        let removed = self
+            .profiles
+            .shift_remove(name)
+            .expect("checked to exist above");
+        if self.default_profile == name {
+            self.default_profile = self
+                .profiles
+                .keys()
+                .next()
+                .expect("at least one profile remains")
+                .clone();
+        }
+        Ok(removed)
+    }
+
+    /// Renames the profile named `old_name` to `new_name`, keeping its position
+    /// and whether it is the default profile.
+    pub fn rename(
+        &mut self,
+        old_name: &str,
+        new_name: impl Into<String>,
+    ) -> Result<(), EmailProfilesError> {
+        let new_name = Self::validated_name(new_name.into())?;
+        let Some(index) = self.profiles.get_index_of(old_name) else {
+            return Err(EmailProfilesError::NotFound {
+                name: old_name.to_owned(),
+            });
+        };
+        if old_name == new_name {
+            return Ok(());
+        }
+        if self.profiles.contains_key(&new_name) {
+            return Err(EmailProfilesError::AlreadyExists { name: new_name });
+        }
+        let (_, settings) = self
+            .profiles
+            .shift_remove_index(index)
+            .expect("index is valid");
+        self.profiles
+            .shift_insert(index, new_name.clone(), settings);
+        if self.default_profile == old_name {
+            self.default_profile = new_name;
+        }
+        Ok(())
+    }
+
+    /// Makes the profile named `name` the default profile.
+    pub fn select(&mut self, name: &str) -> Result<(), EmailProfilesError> {
+        if !self.profiles.contains_key(name) {
+            return Err(EmailProfilesError::NotFound {
+                name: name.to_owned(),
+            });
+        }
+        self.default_profile = name.to_owned();
+        Ok(())
+    }
+
+    pub fn sample() -> Self {
+        let mut profiles =
+            Self::new("billing", EncryptedEmailSettings::sample()).expect("valid profile name");
+        profiles
+            .add("support", EncryptedEmailSettings::sample_other())
+            .expect("unique profile name");
+        profiles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_existing_fails() {
+        let mut profiles = EmailProfiles::sample();
+        assert_eq!(
+            profiles.add("billing", EncryptedEmailSettings::sample_other()),
+            Err(EmailProfilesError::AlreadyExists {
+                name: "billing".to_owned()
+            })
+        );
+    }
+
+    #[test]
+    fn add_empty_name_fails() {
+        let mut profiles = EmailProfiles::sample();
+        assert_eq!(
+            profiles.add(" ", EncryptedEmailSettings::sample_other()),
+            Err(EmailProfilesError::InvalidName {
+                name: " ".to_owned()
+            })
+        );
+    }
+
+    #[test]
+    fn remove_default_selects_first_remaining() {
+        let mut profiles = EmailProfiles::sample();
+        profiles
+            .add("noreply", EncryptedEmailSettings::sample())
+            .unwrap();
+        profiles.remove("billing").unwrap();
+        assert_eq!(profiles.default_profile_name(), "support");
+        assert_eq!(profiles.names().collect::<Vec<_>>(), ["support", "noreply"]);
+    }
+
+    #[test]
+    fn remove_last_profile_fails() {
+        let mut profiles = EmailProfiles::new("billing", EncryptedEmailSettings::sample()).unwrap();
+        assert_eq!(
+            profiles.remove("billing"),
+            Err(EmailProfilesError::CannotRemoveLastProfile)
+        );
+    }
+
+    #[test]
+    fn rename_keeps_position_and_default() {
+        let mut profiles = EmailProfiles::sample();
+        profiles.rename("billing", "invoices").unwrap();
+        assert_eq!(
+            profiles.names().collect::<Vec<_>>(),
+            ["invoices", "support"]
+        );
+        assert_eq!(profiles.default_profile_name(), "invoices");
+        assert_eq!(
+            profiles.default_profile(),
+            &EncryptedEmailSettings::sample()
+        );
+    }
+
+    #[test]
+    fn rename_to_existing_fails() {
+        let mut profiles = EmailProfiles::sample();
+        assert_eq!(
+            profiles.rename("billing", "support"),
+            Err(EmailProfilesError::AlreadyExists {
+                name: "support".to_owned()
+            })
+        );
+    }
+
+    #[test]
+    fn select() {
+        let mut profiles = EmailProfiles::sample();
+        profiles.select("support").unwrap();
+        assert_eq!(
+            profiles.default_profile(),
+            &EncryptedEmailSettings::sample_other()
+        );
+        assert_eq!(
+            profiles.select("marketing"),
+            Err(EmailProfilesError::NotFound {
+                name: "marketing".to_owned()
+            })
+        );
+    }
+
+    #[test]
+    fn json_roundtrip() {
+        let profiles = EmailProfiles::sample();
+        let json = serde_json::to_string(&profiles).unwrap();
+        assert_eq!(
+            serde_json::from_str::<EmailProfiles>(&json).unwrap(),
+            profiles
+        );
+    }
+
+    #[test]
+    fn deserialize_missing_default_fails() {
+        let json = serde_json::json!({
+            "default_profile": "marketing",
+            "profiles": { "billing": EncryptedEmailSettings::sample() },
+        });
+        assert!(serde_json::from_value::<EmailProfiles>(json).is_err());
+    }
+}
@@ -2,8 +2,8 @@ use secrecy::SecretString;
 use zeroize::{Zeroize, ZeroizeOnDrop};
 
 use crate::{
-    Attachment, Email, EmailAccount, EmailAddress, EmailCredentials, EncryptedAppPassword,
-    EncryptionKey, PbHkdfSha256, Salt, SmtpServer, Template,
+    Attachment, Email, EmailAccount, EmailBody, EmailCredentials, EncryptedAppPassword, Recipient,
+    Salt, SmtpServer, Template, TemplateBuiltins, TemplateError, TemplateVariables,
 };
 use bon::Builder;
 use getset::{Getters, WithSetters};
@@ -29,6 +29,7 @@ pub struct EmailSettings<AppPassword: Zeroize> {
     #[getset(get = "pub")]
     template: Template,
 
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     #[getset(get = "pub")]
     reply_to: Option<EmailAccount>,
 
@@ -39,13 +40,13 @@ pub struct EmailSettings<AppPassword: Zeroize> {
     sender: EmailAccount,
 
     #[getset(get = "pub")]
-    recipients: IndexSet<EmailAddress>,
+    recipients: IndexSet<Recipient>,
 
     #[getset(get = "pub")]
-    cc_recipients: IndexSet<EmailAddress>,
+    cc_recipients: IndexSet<Recipient>,
 
     #[getset(get = "pub")]
-    bcc_recipients: IndexSet<EmailAddress>,
+    bcc_recipients: IndexSet<Recipient>,
 }
 
 impl<AppPassword: Zeroize> Zeroize for EmailSettings<AppPassword> {
@@ -64,25 +65,73 @@ impl<AppPassword: Zeroize> Drop for EmailSettings<AppPassword> {
 impl<AppPassword: Zeroize> ZeroizeOnDrop for EmailSettings<AppPassword> {}
 
 impl DecryptedEmailSettings {
+    /// Composes an email to the configured recipients, `body` can be plain text
+    /// (any string) or an [`EmailBody`] with an HTML part.
     pub fn compose(
         &self,
         subject: impl Into<String>,
-        body: impl Into<String>,
+        body: impl Into<EmailBody>,
         attachments: IndexSet<Attachment>,
     ) -> (Email, EmailCredentials) {
+        self.compose_with_message_id(subject, body, attachments, None)
+    }
+
+    fn compose_with_message_id(
+        &self,
+        subject: impl Into<String>,
+        body: impl Into<EmailBody>,
+        attachments: IndexSet<Attachment>,
+        message_id: Option<String>,
+    ) -> (Email, EmailCredentials) {
+        let (text_body, html_body) = body.into().into_parts();
         let email = Email::builder()
             .subject(subject.into())
-            .body(body.into())
+            .maybe_body(text_body)
+            .maybe_html_body(html_body)
             .maybe_reply_to(self.reply_to().clone())
             .public_recipients(self.recipients().clone())
             .cc_recipients(self.cc_recipients().clone())
             .bcc_recipients(self.bcc_recipients().clone())
             .attachments(attachments)
+            .maybe_message_id(message_id)
             .build();
         let credentials = EmailCredentials::from(self.clone());
         (email, credentials)
     }
 
+    /// Renders the template with `variables` and composes the email, see
+    /// [`DecryptedEmailSettings::compose`].
+    ///
+    /// The sender and recipient of `builtins` default to the configured sender
+    /// and first recipient, so `<RECIPIENT_NAME>` is the name of the first
+    /// recipient only. The email's `Message-ID` is the `<MESSAGE_ID>` the
+    /// template is rendered with, which `variables` may override.
+    pub fn render_email(
+        &self,
+        variables: &TemplateVariables,
+        builtins: TemplateBuiltins,
+        attachments: IndexSet<Attachment>,
+    ) -> Result<(Email, EmailCredentials), TemplateError> {
+        let sender = builtins
+            .sender()
+            .clone()
+            .unwrap_or_else(|| self.sender.clone());
+        let recipient = builtins
+            .recipient()
+            .clone()
+            .or_else(|| self.recipients.first().cloned());
+        let builtins = builtins
+            .with_sender(Some(sender))
+            .with_recipient(recipient)
+            .variables();
+        let (subject, body) = self.template.render_with_builtins(variables, &builtins)?;
+        let message_id = variables
+            .get(TemplateBuiltins::MESSAGE_ID)
+            .or_else(|| builtins.get(TemplateBuiltins::MESSAGE_ID))
+            .map(ToString::to_string);
+        Ok(self.compose_with_message_id(subject, body, attachments, message_id))
+    }
+
     pub fn sample() -> Self {
         Self::builder()
             .smtp_app_password(SecretString::from("encryption password"))
@@ -91,13 +140,13 @@ impl DecryptedEmailSettings {
             .smtp_server(SmtpServer::default())
             .sender(EmailAccount::sample())
             .recipients(IndexSet::from([
-                EmailAddress::sample_alice(),
-                EmailAddress::sample_bob(),
+                Recipient::sample_alice(),
+                Recipient::sample_bob(),
             ]))
-            .cc_recipients(IndexSet::from([EmailAddress::sample_carol()]))
+            .cc_recipients(IndexSet::from([Recipient::sample_carol()]))
             .bcc_recipients(IndexSet::from([
-                EmailAddress::sample_dave(),
-                EmailAddress::sample_erin(),
+                Recipient::sample_dave(),
+                Recipient::sample_erin(),
             ]))
             .build()
     }
@@ -110,25 +159,45 @@ impl DecryptedEmailSettings {
             .smtp_server(SmtpServer::default())
             .sender(EmailAccount::sample_other())
             .recipients(IndexSet::from([
-                EmailAddress::sample_bob(),
-                EmailAddress::sample_carol(),
+                Recipient::sample_bob(),
+                Recipient::sample_carol(),
             ]))
-            .cc_recipients(IndexSet::from([EmailAddress::sample_dave()]))
+            .cc_recipients(IndexSet::from([Recipient::sample_dave()]))
             .bcc_recipients(IndexSet::from([
-                EmailAddress::sample_erin(),
-                EmailAddress::sample_alice(),
+                Recipient::sample_erin(),
+                Recipient::sample_alice(),
             ]))
             .build()
     }
 }
 
 impl EncryptedEmailSettings {
-    fn derive_and_decrypt_smtp_app_password(
-        &self,
-        encryption_key: EncryptionKey,
-    ) -> crate::CryptoResult<DecryptedEmailSettings> {
-        let decrypted = self.smtp_app_password.decrypt(encryption_key)?;
-        Ok(DecryptedEmailSettings::builder()
+    /// Associated data binding an encrypted app password to the sender's
+    /// email address and the SMTP server host, so that it cannot be copied
+    /// into settings of another sender or server.
+    ///
+    /// The port and TLS mode are left out: they select how to connect to the
+    /// same server rather than which account the password belongs to, so
+    /// switching e.g. from port 465 to 587 keeps the stored password valid.
+    pub fn app_password_associated_data_for(
+        sender: &EmailAccount,
+        smtp_server: &SmtpServer,
+    ) -> Vec<u8> {
+        format!(
+            "mejla app password\0{}\0{}",
+            sender.email(),
+            smtp_server.host()
+        )
+        .into_bytes()
+    }
+
+    /// Associated data binding the encrypted app password to the sender and SMTP server of these settings.
+    pub fn app_password_associated_data(&self) -> Vec<u8> {
+        Self::app_password_associated_data_for(&self.sender, &self.smtp_server)
+    }
+
+    fn with_decrypted_smtp_app_password(&self, decrypted: SecretString) -> DecryptedEmailSettings {
+        DecryptedEmailSettings::builder()
             .smtp_app_password(decrypted)
             .maybe_reply_to(self.reply_to.clone())
             .smtp_server(self.smtp_server.clone())
@@ -138,15 +207,52 @@ impl EncryptedEmailSettings {
             .bcc_recipients(self.bcc_recipients.clone())
             .template(self.template.clone())
             .salt(self.salt().clone())
-            .build())
+            .build()
     }
 
+    /// Decrypts the SMTP app password, failing with [`crate::CryptoError::AssociatedDataMismatch`]
+    /// if it was encrypted for another sender or SMTP server.
     pub fn decrypt_smtp_app_password(
         &self,
         encryption_password: SecretString,
     ) -> crate::CryptoResult<DecryptedEmailSettings> {
-        let encryption_key = PbHkdfSha256::derive_key_from(encryption_password, self.salt());
-        self.derive_and_decrypt_smtp_app_password(encryption_key)
+        let decrypted = self.smtp_app_password.derive_and_decrypt(
+            encryption_password,
+            self.salt(),
+            &self.app_password_associated_data(),
+        )?;
+        Ok(self.with_decrypted_smtp_app_password(decrypted))
+    }
+
+    /// Changes the encryption password from `old_encryption_password` to
+    /// `new_encryption_password`, without having to re-enter the app password.
+    ///
+    /// The app password is decrypted and re-encrypted under a freshly generated
+    /// [`Salt`], using the default [`crate::KeyDerivation`].
+    pub fn rekey(
+        &self,
+        old_encryption_password: SecretString,
+        new_encryption_password: SecretString,
+    ) -> crate::CryptoResult<Self> {
+        let decrypted = self.decrypt_smtp_app_password(old_encryption_password)?;
+        let salt = Salt::generate();
+        let smtp_app_password = EncryptedAppPassword::new_by_deriving_and_encrypting(
+            decrypted.smtp_app_password().clone(),
+            new_encryption_password,
+            &salt,
+            &self.app_password_associated_data(),
+        );
+        Ok(Self::builder()
+            .smtp_app_password(smtp_app_password)
+            .salt(salt)
+            .maybe_reply_to(self.reply_to.clone())
+            .smtp_server(self.smtp_server.clone())
+            .sender(self.sender.clone())
+            .recipients(self.recipients.clone())
+            .cc_recipients(self.cc_recipients.clone())
+            .bcc_recipients(self.bcc_recipients.clone())
+            .template(self.template.clone())
+            .build())
     }
 
     pub fn sample() -> Self {
@@ -157,13 +263,13 @@ impl EncryptedEmailSettings {
             .smtp_server(SmtpServer::default())
             .sender(EmailAccount::sample())
             .recipients(IndexSet::from([
-                EmailAddress::sample_alice(),
-                EmailAddress::sample_bob(),
+                Recipient::sample_alice(),
+                Recipient::sample_bob(),
             ]))
-            .cc_recipients(IndexSet::from([EmailAddress::sample_carol()]))
+            .cc_recipients(IndexSet::from([Recipient::sample_carol()]))
             .bcc_recipients(IndexSet::from([
-                EmailAddress::sample_dave(),
-                EmailAddress::sample_erin(),
+                Recipient::sample_dave(),
+                Recipient::sample_erin(),
             ]))
             .build()
     }
@@ -176,14 +282,194 @@ impl EncryptedEmailSettings {
             .smtp_server(SmtpServer::default())
             .sender(EmailAccount::sample_other())
             .recipients(IndexSet::from([
-                EmailAddress::sample_bob(),
-                EmailAddress::sample_carol(),
+                Recipient::sample_bob(),
+                Recipient::sample_carol(),
             ]))
-            .cc_recipients(IndexSet::from([EmailAddress::sample_dave()]))
+            .cc_recipients(IndexSet::from([Recipient::sample_dave()]))
             .bcc_recipients(IndexSet::from([
-                EmailAddress::sample_erin(),
-                EmailAddress::sample_alice(),
+                Recipient::sample_erin(),
+                Recipient::sample_alice(),
             ]))
             .build()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CryptoError, KeyDerivation};
+
+    fn encrypted_settings(sender: EmailAccount) -> EncryptedEmailSettings {
+        let salt = Salt::sample();
+        let smtp_app_password = EncryptedAppPassword::new_by_deriving_and_encrypting_with(
+            SecretString::from("app password"),
+            SecretString::from("open sesame"),
+            &salt,
+            KeyDerivation::HkdfSha256,
+            &EncryptedEmailSettings::app_password_associated_data_for(
+                &sender,
+                &SmtpServer::default(),
+            ),
+        )
+        .unwrap();
+        settings_with(sender, smtp_app_password)
+    }
+
+    fn settings_with(
+        sender: EmailAccount,
+        smtp_app_password: EncryptedAppPassword,
+    ) -> EncryptedEmailSettings {
+        EncryptedEmailSettings::builder()
+            .smtp_app_password(smtp_app_password)
+            .salt(Salt::sample())
+            .template(Template::default())
+            .smtp_server(SmtpServer::default())
+            .sender(sender)
+            .recipients(IndexSet::from([Recipient::sample_bob()]))
+            .cc_recipients(IndexSet::new())
+            .bcc_recipients(IndexSet::new())
+            .build()
+    }
+
+    #[test]
+    fn render_email_with_builtins() {
+        let settings = invoice_settings();
+        let builtins = TemplateBuiltins::builder().seed(1).build();
+        let (email, _) = settings
+            .render_email(
+                &TemplateVariables::from([("INV_NO", 42)]),
+                builtins,
+                IndexSet::new(),
+            )
+            .unwrap();
+        let message_id = email.message_id().clone().unwrap();
+        assert!(message_id.ends_with("@example.com"));
+        assert_eq!(
+            email.body(),
+            format!("Hi Bob Johnson, ref {message_id}. /Alice Smith")
+        );
+    }
+
+    #[test]
+    fn render_email_with_overridden_message_id() {
+        let variables = TemplateVariables::from([
+            ("INV_NO", crate::TemplateValue::from(42)),
+            (
+                TemplateBuiltins::MESSAGE_ID,
+                "invoice-42@example.com".into(),
+            ),
+        ]);
+        let (email, _) = invoice_settings()
+            .render_email(
+                &variables,
+                TemplateBuiltins::builder().seed(1).build(),
+                IndexSet::new(),
+            )
+            .unwrap();
+        assert_eq!(
+            email.message_id().as_deref(),
+            Some("invoice-42@example.com")
+        );
+        assert_eq!(
+            email.body(),
+            "Hi Bob Johnson, ref invoice-42@example.com. /Alice Smith"
+        );
+    }
+
+    fn invoice_settings() -> DecryptedEmailSettings {
+        DecryptedEmailSettings::builder()
+            .smtp_app_password(SecretString::from("app password"))
+            .salt(Salt::sample())
+            .template(
+                Template::builder()
+                    .subject_format("Invoice <INV_NO>".into())
+                    .body_format("Hi <RECIPIENT_NAME>, ref <MESSAGE_ID>. /<SENDER_NAME>".into())
+                    .build(),
+            )
+            .smtp_server(SmtpServer::default())
+            .sender(EmailAccount::sample_alice())
+            .recipients(IndexSet::from([Recipient::sample_bob()]))
+            .cc_recipients(IndexSet::new())
+            .bcc_recipients(IndexSet::new())
+            .build()
+    }
+
+    #[test]
+    fn decrypt_smtp_app_password() {
+        let settings = encrypted_settings(EmailAccount::sample_alice());
+        let decrypted = settings
+            .decrypt_smtp_app_password(SecretString::from("open sesame"))
+            .unwrap();
+        assert_eq!(
+            secrecy::ExposeSecret::expose_secret(decrypted.smtp_app_password()),
+            "app password"
+        );
+    }
+
+    #[test]
+    fn decrypt_smtp_app_password_copied_to_other_sender_fails() {
+        let alice = encrypted_settings(EmailAccount::sample_alice());
+        let tampered = settings_with(
+            EmailAccount::sample_bob(),
+            alice.smtp_app_password().clone(),
+        );
+        assert!(matches!(
+            tampered.decrypt_smtp_app_password(SecretString::from("open sesame")),
+            Err(CryptoError::AssociatedDataMismatch)
+        ));
+    }
+
+    #[test]
+    fn decrypt_legacy_unbound_smtp_app_password() {
+        let key = crate::PbHkdfSha256::derive_key_from(
+            SecretString::from("open sesame"),
+            &Salt::sample(),
+        );
+        let legacy = crate::AesGcm256::seal("app password", key).combined();
+        let legacy: EncryptedAppPassword =
+            serde_json::from_value(serde_json::Value::String(hex::encode(legacy))).unwrap();
+        let settings = settings_with(EmailAccount::sample_bob(), legacy);
+        assert!(
+            settings
+                .decrypt_smtp_app_password(SecretString::from("open sesame"))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn rekey() {
+        let settings = encrypted_settings(EmailAccount::sample_alice());
+        let rekeyed = settings
+            .rekey(
+                SecretString::from("open sesame"),
+                SecretString::from("new password"),
+            )
+            .unwrap();
+        assert_ne!(rekeyed.salt(), settings.salt());
+        assert_eq!(rekeyed.sender(), settings.sender());
+        assert!(matches!(
+            rekeyed.decrypt_smtp_app_password(SecretString::from("open sesame")),
+            Err(CryptoError::AesDecryptionFailed)
+        ));
+        let decrypted = rekeyed
+            .decrypt_smtp_app_password(SecretString::from("new password"))
+            .unwrap();
+        assert_eq!(
+            secrecy::ExposeSecret::expose_secret(decrypted.smtp_app_password()),
+            "app password"
+        );
+    }
+
+    #[test]
+    fn rekey_with_wrong_old_password_fails() {
+        let settings = encrypted_settings(EmailAccount::sample_alice());
+        assert!(
+            settings
+                .rekey(
+                    SecretString::from("wrong"),
+                    SecretString::from("new password"),
+                )
+                .is_err()
+        );
+    }
+}
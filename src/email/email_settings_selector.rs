@@ -24,12 +24,16 @@ pub enum EmailSettingsSelector {
 
 impl EmailSettingsSelector {
     /// Returns whether selecting this field requires the encryption password.
+    ///
+    /// The SMTP server and sender require it since the encrypted app password
+    /// is bound to them, and must thus be re-encrypted when they change. Only
+    /// the current encryption password is needed for that, the app password is
+    /// decrypted and re-encrypted without being re-entered.
     pub fn requires_encryption_password(&self) -> bool {
         use EmailSettingsSelector::*;
         match self {
-            All | AppPassword | EncryptionPassword => true,
-            Template | SmtpServer | ReplyTo | Sender | Recipients | CcRecipients
-            | BccRecipients => false,
+            All | AppPassword | EncryptionPassword | SmtpServer | Sender => true,
+            Template | ReplyTo | Recipients | CcRecipients | BccRecipients => false,
         }
     }
 
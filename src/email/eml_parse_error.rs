@@ -0,0 +1,37 @@
+use std::{
+    fmt::{Display, Formatter},
+    path::PathBuf,
+};
+
+/// Errors that can occur while parsing an `.eml` file with [`crate::ParsedEml`].
+#[derive(Debug)]
+pub enum EmlParseError {
+    /// The file at `path` could not be read.
+    Read {
+        path: PathBuf,
+        underlying: std::io::Error,
+    },
+
+    /// The data is not an RFC 5322 message, e.g. it has no headers.
+    NotAMessage,
+}
+
+impl Display for EmlParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Read { path, underlying } => {
+                write!(f, "failed to read {}: {underlying}", path.display())
+            }
+            Self::NotAMessage => write!(f, "not an RFC 5322 email message"),
+        }
+    }
+}
+
+impl std::error::Error for EmlParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Read { underlying, .. } => Some(underlying),
+            Self::NotAMessage => None,
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use std::{
+    io::{BufWriter, ErrorKind},
+    path::{Path, PathBuf},
+};
+
+use lettre::transport::smtp::response::Response;
+
+use crate::{FormattedMessage, MailTransport, SendEmailError, SendPolicy, ok_response_with};
+
+/// A transport which writes every message as an `.eml` file into a directory,
+/// the response message is the path of the written file.
+///
+/// Attachments are encoded while they are written, so they are never held in
+/// memory. Each message is written to a temporary file in the directory first and then
+/// moved to a fresh random name, so readers of the directory never see partially
+/// written files and an existing file is never overwritten.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileMailTransport {
+    directory: PathBuf,
+    policy: SendPolicy,
+}
+
+impl FileMailTransport {
+    /// Creates a transport writing into `directory`, which must already exist.
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+            policy: SendPolicy::default(),
+        }
+    }
+
+    /// Replaces the default policy, e.g. with the one of the SMTP server the
+    /// messages would otherwise be sent to.
+    pub fn with_send_policy(self, policy: SendPolicy) -> Self {
+        Self { policy, ..self }
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+}
+
+impl MailTransport for FileMailTransport {
+    fn send_message(&self, message: &FormattedMessage) -> Result<Response, SendEmailError> {
+        let mut file =
+            tempfile::NamedTempFile::new_in(&self.directory).map_err(SendEmailError::WriteEmail)?;
+        message
+            .write_to(BufWriter::new(file.as_file()))
+            .and_then(|()| file.as_file().sync_all())
+            .map_err(SendEmailError::WriteEmail)?;
+        loop {
+            let path = self
+                .directory
+                .join(format!("{:016x}.eml", rand::random::<u64>()));
+            match file.persist_noclobber(&path) {
+                Ok(_) => return Ok(ok_response_with(path.display().to_string())),
+                Err(error) if error.error.kind() == ErrorKind::AlreadyExists => file = error.file,
+                Err(error) => return Err(SendEmailError::WriteEmail(error.error)),
+            }
+        }
+    }
+
+    fn send_policy(&self) -> SendPolicy {
+        self.policy.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Email, EmailAccount, send_email_with_transport, send_email_with_transport_archived,
+    };
+
+    #[test]
+    fn writes_eml_file() {
+        let directory = tempfile::tempdir().unwrap();
+        let transport = FileMailTransport::new(directory.path());
+
+        let sent =
+            send_email_with_transport_archived(Email::sample(), EmailAccount::sample(), &transport)
+                .unwrap();
+
+        let files = std::fs::read_dir(directory.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect::<Vec<_>>();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].extension().unwrap(), "eml");
+        assert_eq!(
+            sent.response().message().collect::<Vec<_>>(),
+            [files[0].display().to_string()]
+        );
+        let contents = std::fs::read(&files[0]).unwrap();
+        assert_eq!(contents, sent.to_eml().unwrap());
+        assert!(
+            String::from_utf8(contents)
+                .unwrap()
+                .contains("Subject: Sample Email Subject")
+        );
+    }
+
+    #[test]
+    fn missing_directory_fails() {
+        let directory = tempfile::tempdir().unwrap();
+        let transport = FileMailTransport::new(directory.path().join("missing"));
+        let result = send_email_with_transport(Email::sample(), EmailAccount::sample(), &transport);
+        assert!(matches!(result, Err(SendEmailError::WriteEmail(_))));
+    }
+}
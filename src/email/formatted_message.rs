@@ -0,0 +1,127 @@
+use std::{
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use lettre::address::Envelope;
+
+use crate::{AttachmentData, SendEmailError};
+
+/// A composed message as it is sent: the SMTP envelope and the message, whose
+/// bytes are also the contents of an `.eml` file.
+///
+/// Attachment content is referenced rather than held, see [`AttachmentData`],
+/// and base64 encoded in chunks while the message is written with
+/// [`FormattedMessage::write_to`]. Writing a message to a file thus never
+/// holds its attachments in memory, and [`FormattedMessage::to_bytes`], which
+/// SMTP transports need as lettre sends from a buffer, holds them once, encoded.
+///
+/// Transports and [`crate::SendPolicy`] work on the same message, so it is
+/// composed once on its way to the server and has the same `Date` and
+/// `Message-ID` headers however often it is written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormattedMessage {
+    envelope: Envelope,
+    parts: Vec<MessagePart>,
+    size: u64,
+}
+
+/// A consecutive piece of a [`FormattedMessage`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum MessagePart {
+    /// Headers, MIME boundaries and bodies as formatted by lettre.
+    Formatted(Vec<u8>),
+
+    /// The base64 encoded content of an attachment.
+    Attachment(AttachmentData),
+}
+
+impl FormattedMessage {
+    /// A message of the `formatted` bytes.
+    pub(crate) fn new(envelope: Envelope, formatted: Vec<u8>) -> Self {
+        Self {
+            envelope,
+            size: formatted.len() as u64,
+            parts: vec![MessagePart::Formatted(formatted)],
+        }
+    }
+
+    /// A message of the `formatted` bytes in which each placeholder of
+    /// `attachments`, in order, stands for the encoded content of its attachment.
+    ///
+    /// Fails if the size of file-backed content cannot be read, e.g. because
+    /// the file does not exist.
+    pub(crate) fn with_attachments(
+        envelope: Envelope,
+        formatted: &[u8],
+        attachments: Vec<(Vec<u8>, AttachmentData)>,
+    ) -> std::io::Result<Self> {
+        let mut parts = Vec::with_capacity(2 * attachments.len() + 1);
+        let mut size = 0;
+        let mut rest = formatted;
+        for (placeholder, data) in attachments {
+            let start = rest
+                .windows(placeholder.len())
+                .position(|window| window == placeholder)
+                .expect("placeholders are formatted in the order they were created");
+            size += start as u64 + AttachmentData::base64_size(data.size()?);
+            parts.push(MessagePart::Formatted(rest[..start].to_vec()));
+            parts.push(MessagePart::Attachment(data));
+            rest = &rest[start + placeholder.len()..];
+        }
+        size += rest.len() as u64;
+        parts.push(MessagePart::Formatted(rest.to_vec()));
+        Ok(Self {
+            envelope,
+            parts,
+            size,
+        })
+    }
+
+    /// The SMTP sender and recipients, including BCC recipients.
+    pub fn envelope(&self) -> &Envelope {
+        &self.envelope
+    }
+
+    /// The size of the message in bytes, computed from the sizes of its
+    /// attachments when it was composed, without encoding them.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Writes the message with headers and encoded body to `writer`.
+    ///
+    /// File-backed attachments are read while writing, so their files must
+    /// still exist, and be unchanged for the message to be
+    /// [`FormattedMessage::size`] bytes.
+    pub fn write_to(&self, mut writer: impl Write) -> std::io::Result<()> {
+        for part in &self.parts {
+            match part {
+                MessagePart::Formatted(bytes) => writer.write_all(bytes)?,
+                MessagePart::Attachment(data) => data.write_base64(&mut writer)?,
+            }
+        }
+        writer.flush()
+    }
+
+    /// The message with headers and encoded body, see [`FormattedMessage::write_to`].
+    pub fn to_bytes(&self) -> std::io::Result<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(self.size as usize);
+        self.write_to(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Writes the message as an `.eml` file to `path`, see [`FormattedMessage::write_to`].
+    pub(crate) fn write_eml(&self, path: &Path) -> Result<(), SendEmailError> {
+        std::fs::File::create(path)
+            .and_then(|file| self.write_to(BufWriter::new(file)))
+            .map_err(SendEmailError::WriteEmail)
+    }
+
+    /// [`FormattedMessage::to_bytes`], failing with [`SendEmailError::CreateEmail`]
+    /// if attachment content cannot be read.
+    pub(crate) fn encode(&self) -> Result<Vec<u8>, SendEmailError> {
+        self.to_bytes()
+            .map_err(|error| SendEmailError::CreateEmail(error.into()))
+    }
+}
@@ -0,0 +1,118 @@
+use std::sync::{Arc, Mutex};
+
+use lettre::{address::Envelope, transport::smtp::response::Response};
+
+use crate::{
+    EmlParseError, FormattedMessage, MailTransport, ParsedEml, SendEmailError, ok_response,
+};
+
+/// A transport which captures every sent message in memory so that it can be
+/// inspected afterwards, useful in tests. Clones share the same captured messages.
+///
+/// [`InMemoryMailTransport::emails`] gives the messages as parsed emails, to
+/// check e.g. their subjects, recipients and attachments, and
+/// [`InMemoryMailTransport::messages`] gives them exactly as they were sent.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryMailTransport {
+    messages: Arc<Mutex<Vec<CapturedMessage>>>,
+}
+
+/// The envelope and bytes of a sent message, captured encoded so that later
+/// changes to file-backed attachments do not alter it.
+type CapturedMessage = (Envelope, Vec<u8>);
+
+impl InMemoryMailTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// All messages sent so far, in the order they were sent.
+    pub fn messages(&self) -> Vec<FormattedMessage> {
+        self.messages
+            .lock()
+            .expect("messages lock not poisoned")
+            .iter()
+            .map(|(envelope, formatted)| FormattedMessage::new(envelope.clone(), formatted.clone()))
+            .collect()
+    }
+
+    /// All messages sent so far parsed back into emails, see [`ParsedEml`].
+    ///
+    /// BCC recipients are not part of a sent message, only of the
+    /// [`FormattedMessage::envelope`] of it.
+    pub fn emails(&self) -> Result<Vec<ParsedEml>, EmlParseError> {
+        self.messages
+            .lock()
+            .expect("messages lock not poisoned")
+            .iter()
+            .map(|(_, formatted)| ParsedEml::parse(formatted))
+            .collect()
+    }
+
+    /// Removes all captured messages.
+    pub fn clear(&self) {
+        self.messages
+            .lock()
+            .expect("messages lock not poisoned")
+            .clear();
+    }
+}
+
+impl MailTransport for InMemoryMailTransport {
+    fn send_message(&self, message: &FormattedMessage) -> Result<Response, SendEmailError> {
+        self.messages
+            .lock()
+            .expect("messages lock not poisoned")
+            .push((message.envelope().clone(), message.encode()?));
+        Ok(ok_response())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DecryptedEmailSettings, Email, EmailAccount, send_email_with_transport};
+    use indexmap::IndexSet;
+
+    #[test]
+    fn captures_composed_email() {
+        let settings = DecryptedEmailSettings::sample();
+        let (email, credentials) = settings.compose("Invoice 42", "Hi!", IndexSet::new());
+        let transport = InMemoryMailTransport::new();
+
+        send_email_with_transport(email, credentials.account().clone(), &transport).unwrap();
+
+        let emails = transport.emails().unwrap();
+        assert_eq!(emails.len(), 1);
+        let sent = emails[0].email();
+        assert_eq!(sent.subject(), "Invoice 42");
+        assert_eq!(emails[0].sender().as_ref(), Some(credentials.account()));
+        assert_eq!(
+            sent.public_recipients()
+                .iter()
+                .map(|recipient| recipient.email().to_string())
+                .collect::<Vec<_>>(),
+            ["alice@example.com", "bob@example.com"]
+        );
+        assert_eq!(sent.body(), "Hi!");
+        assert!(sent.bcc_recipients().is_empty());
+
+        let messages = transport.messages();
+        assert_eq!(
+            messages[0].envelope().to().len(),
+            settings.recipients().len()
+                + settings.cc_recipients().len()
+                + settings.bcc_recipients().len()
+        );
+    }
+
+    #[test]
+    fn clones_share_messages() {
+        let transport = InMemoryMailTransport::new();
+        let clone = transport.clone();
+        send_email_with_transport(Email::sample(), EmailAccount::sample(), &clone).unwrap();
+        assert_eq!(transport.messages().len(), 1);
+        transport.clear();
+        assert!(clone.messages().is_empty());
+    }
+}
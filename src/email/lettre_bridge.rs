@@ -1,15 +1,30 @@
-use std::fmt::{Display, Formatter};
+use std::{
+    fmt::{Display, Formatter},
+    path::Path,
+};
 
 use bon::Builder;
 use getset::Getters;
 use lettre::{
-    Message, SmtpTransport, Transport,
-    message::{Mailbox, MultiPart, SinglePart, header::ContentType},
-    transport::smtp::{authentication::Credentials, response::Response},
+    AsyncSmtpTransport, Message, SmtpTransport, Tokio1Executor,
+    message::{
+        Body, Mailbox, MultiPart, SinglePart,
+        header::{ContentTransferEncoding, ContentType},
+    },
+    transport::smtp::{
+        AsyncSmtpTransportBuilder, SmtpTransportBuilder,
+        authentication::Credentials,
+        client::{Tls, TlsParameters},
+        response::Response,
+    },
 };
 use secrecy::ExposeSecret;
 
-use crate::{Attachment, Email, EmailAccount, EmailAddress, EmailCredentials};
+use crate::{
+    AsyncSmtpMailTransport, Attachment, AttachmentData, DeliveryMode, Email, EmailAccount,
+    EmailAddress, EmailCredentials, FileMailTransport, FormattedMessage, MailTransport, SendPolicy,
+    SendPolicyViolation, SentEmail, SmtpMailTransport, SmtpServer, TlsMode,
+};
 
 /// Ephemeral helper struct to hold an email and sender while building `lettre::Message`.
 #[derive(Debug, Clone, Builder, Getters)]
@@ -21,11 +36,41 @@ pub struct EmailWithSender {
     sender: EmailAccount,
 }
 
+impl EmailWithSender {
+    /// Builds the `lettre::Message`, which holds the encoded content of all
+    /// attachments. Sending refers to the content instead, see [`FormattedMessage`].
+    pub fn to_message(&self) -> Result<Message, SendEmailError> {
+        compose_message(self.email.clone(), self.sender.clone())
+    }
+
+    /// The raw RFC 5322 message, i.e. the content of an `.eml` file, which
+    /// can be archived or opened in a mail client.
+    ///
+    /// The `Date` and, unless set on the email, `Message-ID` headers are
+    /// generated anew on each call, so this is not the message that was sent.
+    /// To archive that, send with e.g. [`send_email_with_transport_archived`]
+    /// and use [`SentEmail::to_eml`].
+    pub fn to_eml(&self) -> Result<Vec<u8>, SendEmailError> {
+        format_message(self.email.clone(), self.sender.clone())?.encode()
+    }
+
+    /// Writes the message as an `.eml` file to `path`, see [`EmailWithSender::to_eml`].
+    ///
+    /// Attachments are encoded while the file is written, so they are never
+    /// held in memory.
+    pub fn write_eml(&self, path: impl AsRef<Path>) -> Result<(), SendEmailError> {
+        format_message(self.email.clone(), self.sender.clone())?.write_eml(path.as_ref())
+    }
+}
+
 #[derive(Debug)]
 pub enum SendEmailError {
     CreateEmail(lettre::error::Error),
     CreateSmtpTransport(lettre::transport::smtp::Error),
     SendEmail(lettre::transport::smtp::Error),
+    WriteEmail(std::io::Error),
+    /// The email was rejected before being sent, see [`SendPolicy`].
+    Policy(SendPolicyViolation),
 }
 
 impl Display for SendEmailError {
@@ -40,31 +85,222 @@ impl Display for SendEmailError {
             Self::SendEmail(error) => {
                 write!(f, "failed to send email: {error}")
             }
+            Self::WriteEmail(error) => {
+                write!(f, "failed to write email to file: {error}")
+            }
+            Self::Policy(violation) => {
+                write!(f, "email rejected before sending: {violation}")
+            }
         }
     }
 }
 
 impl std::error::Error for SendEmailError {}
 
-pub fn send_email_with_credentials(
+/// Builds the `lettre::Message` for `email` sent from `sender`.
+pub(crate) fn compose_message(
     email: Email,
-    credentials: EmailCredentials,
-) -> Result<Response, SendEmailError> {
+    sender: EmailAccount,
+) -> Result<Message, SendEmailError> {
     let email_with_sender = EmailWithSender::builder()
         .email(email)
-        .sender(credentials.account().clone())
+        .sender(sender)
         .build();
-    let email = Message::try_from(email_with_sender).map_err(SendEmailError::CreateEmail)?;
-
-    let smtp_server = credentials.smtp_server().clone();
-    let creds = Credentials::from(credentials);
+    Message::try_from(email_with_sender).map_err(SendEmailError::CreateEmail)
+}
 
-    let mailer = SmtpTransport::relay(smtp_server.as_ref())
-        .map_err(SendEmailError::CreateSmtpTransport)?
-        .credentials(creds)
+/// Composes `email` from `sender` into a [`FormattedMessage`], which refers
+/// to the content of attachments instead of holding it encoded.
+pub(crate) fn format_message(
+    email: Email,
+    sender: EmailAccount,
+) -> Result<FormattedMessage, SendEmailError> {
+    let email_with_sender = EmailWithSender::builder()
+        .email(email)
+        .sender(sender)
         .build();
+    // Attachment bodies are formatted as placeholders, unique to the message,
+    // which stand for the encoded content when the message is written.
+    let token = rand::random::<u128>();
+    let mut attachments = Vec::new();
+    let message = build_message(&email_with_sender, &mut |data| {
+        let placeholder =
+            format!("mejla-attachment-{token:032x}-{}", attachments.len()).into_bytes();
+        attachments.push((placeholder.clone(), data.clone()));
+        Ok(Body::dangerous_pre_encoded(
+            placeholder,
+            ContentTransferEncoding::Base64,
+        ))
+    })
+    .map_err(SendEmailError::CreateEmail)?;
+    FormattedMessage::with_attachments(
+        message.envelope().clone(),
+        &message.formatted(),
+        attachments,
+    )
+    .map_err(|error| SendEmailError::CreateEmail(error.into()))
+}
+
+/// Expands to a `Result` holding the builder of `$transport` configured with the host, port,
+/// TLS mode and timeout of `$smtp_server`.
+///
+/// The blocking and async transports expose the same builder methods without a common trait.
+macro_rules! configured_transport_builder {
+    ($transport:ty, $smtp_server:expr) => {{
+        let smtp_server: &SmtpServer = $smtp_server;
+        let host = smtp_server.host();
+        let builder = match smtp_server.tls() {
+            TlsMode::Implicit => <$transport>::relay(host),
+            TlsMode::StartTls => <$transport>::starttls_relay(host),
+            TlsMode::Opportunistic => TlsParameters::new(host.clone()).map(|parameters| {
+                <$transport>::builder_dangerous(host).tls(Tls::Opportunistic(parameters))
+            }),
+            TlsMode::None => Ok(<$transport>::builder_dangerous(host)),
+        };
+        builder.map(|builder| {
+            let builder = builder.port(smtp_server.port());
+            match smtp_server.timeout() {
+                Some(timeout) => builder.timeout(Some(timeout)),
+                None => builder,
+            }
+        })
+    }};
+}
+
+/// Creates a blocking SMTP transport builder for the host, port, TLS mode and timeout of `smtp_server`.
+pub(crate) fn smtp_transport_builder(
+    smtp_server: &SmtpServer,
+) -> Result<SmtpTransportBuilder, lettre::transport::smtp::Error> {
+    configured_transport_builder!(SmtpTransport, smtp_server)
+}
+
+/// Creates an async SMTP transport builder for the host, port, TLS mode and timeout of `smtp_server`.
+pub(crate) fn async_smtp_transport_builder(
+    smtp_server: &SmtpServer,
+) -> Result<AsyncSmtpTransportBuilder, lettre::transport::smtp::Error> {
+    configured_transport_builder!(AsyncSmtpTransport<Tokio1Executor>, smtp_server)
+}
+
+/// Composes `email` from `sender`, formats it and checks it with `policy`.
+fn compose_checked_message(
+    email: Email,
+    sender: EmailAccount,
+    policy: &SendPolicy,
+) -> Result<FormattedMessage, SendEmailError> {
+    policy.check_email(&email).map_err(SendEmailError::Policy)?;
+    let message = format_message(email, sender)?;
+    policy
+        .check_message(&message)
+        .map_err(SendEmailError::Policy)?;
+    Ok(message)
+}
+
+/// Sends `email` from `sender` using the given `transport`, after checking it
+/// with the [`MailTransport::send_policy`] of the transport.
+pub fn send_email_with_transport(
+    email: Email,
+    sender: EmailAccount,
+    transport: &impl MailTransport,
+) -> Result<Response, SendEmailError> {
+    send_email_with_transport_archived(email, sender, transport).map(SentEmail::into_response)
+}
 
-    mailer.send(&email).map_err(SendEmailError::SendEmail)
+/// Like [`send_email_with_transport`], but also returns the message as it
+/// was sent, e.g. to archive it, see [`SentEmail`].
+pub fn send_email_with_transport_archived(
+    email: Email,
+    sender: EmailAccount,
+    transport: &impl MailTransport,
+) -> Result<SentEmail, SendEmailError> {
+    let message = compose_checked_message(email, sender, &transport.send_policy())?;
+    let response = transport.send_message(&message)?;
+    Ok(SentEmail::new(response, message))
+}
+
+/// Sends `email` as `mode` says, either using the blocking SMTP transport,
+/// authenticating with `credentials`, or as a dry run writing the message to
+/// a file, in which case the SMTP server is never contacted.
+///
+/// Both modes check the email with the [`SendPolicy`] of the SMTP server.
+pub fn send_email_with_delivery_mode(
+    email: Email,
+    credentials: EmailCredentials,
+    mode: &DeliveryMode,
+) -> Result<Response, SendEmailError> {
+    send_email_with_delivery_mode_archived(email, credentials, mode).map(SentEmail::into_response)
+}
+
+/// Like [`send_email_with_delivery_mode`], but also returns the message as it
+/// was sent or written, e.g. to archive it, see [`SentEmail`].
+pub fn send_email_with_delivery_mode_archived(
+    email: Email,
+    credentials: EmailCredentials,
+    mode: &DeliveryMode,
+) -> Result<SentEmail, SendEmailError> {
+    let sender = credentials.account().clone();
+    match mode {
+        DeliveryMode::Smtp => {
+            let transport = SmtpMailTransport::try_from(credentials)?;
+            send_email_with_transport_archived(email, sender, &transport)
+        }
+        DeliveryMode::DryRun { directory } => {
+            let transport = FileMailTransport::new(directory)
+                .with_send_policy(SendPolicy::for_smtp_server(credentials.smtp_server()));
+            send_email_with_transport_archived(email, sender, &transport)
+        }
+    }
+}
+
+/// Sends `email` using the blocking SMTP transport, authenticating with `credentials`.
+pub fn send_email_with_credentials(
+    email: Email,
+    credentials: EmailCredentials,
+) -> Result<Response, SendEmailError> {
+    let sender = credentials.account().clone();
+    let transport = SmtpMailTransport::try_from(credentials)?;
+    send_email_with_transport(email, sender, &transport)
+}
+
+/// Sends `email` from `sender` using the async SMTP `transport`, after checking
+/// it with the [`AsyncSmtpMailTransport::send_policy`] of the transport.
+///
+/// Must be awaited from within a tokio runtime.
+pub async fn send_email_with_async_transport(
+    email: Email,
+    sender: EmailAccount,
+    transport: &AsyncSmtpMailTransport,
+) -> Result<Response, SendEmailError> {
+    send_email_with_async_transport_archived(email, sender, transport)
+        .await
+        .map(SentEmail::into_response)
+}
+
+/// Like [`send_email_with_async_transport`], but also returns the message as
+/// it was sent, e.g. to archive it, see [`SentEmail`].
+///
+/// Must be awaited from within a tokio runtime.
+pub async fn send_email_with_async_transport_archived(
+    email: Email,
+    sender: EmailAccount,
+    transport: &AsyncSmtpMailTransport,
+) -> Result<SentEmail, SendEmailError> {
+    let message = compose_checked_message(email, sender, transport.send_policy())?;
+    let response = transport.send_message(&message).await?;
+    Ok(SentEmail::new(response, message))
+}
+
+/// Sends `email` using the async (tokio) SMTP transport, authenticating with
+/// `credentials`, see [`send_email_with_async_transport`] to send with
+/// another [`SendPolicy`].
+///
+/// Must be awaited from within a tokio runtime.
+pub async fn send_email_with_credentials_async(
+    email: Email,
+    credentials: EmailCredentials,
+) -> Result<Response, SendEmailError> {
+    let sender = credentials.account().clone();
+    let transport = AsyncSmtpMailTransport::try_from(credentials)?;
+    send_email_with_async_transport(email, sender, &transport).await
 }
 
 trait CommonContentType: Sized {
@@ -77,16 +313,42 @@ impl CommonContentType for ContentType {
     }
 }
 
-impl From<Attachment> for SinglePart {
-    fn from(attachment: Attachment) -> Self {
-        let content_type = ContentType::parse(attachment.mime_type())
-            .unwrap_or_else(|_| ContentType::octet_stream());
+/// Base64 encodes `data` into a body, see [`AttachmentData::write_base64`].
+fn base64_body(data: &AttachmentData) -> std::io::Result<Body> {
+    let mut encoded = Vec::with_capacity(AttachmentData::base64_size(data.size()?) as usize);
+    data.write_base64(&mut encoded)?;
+    Ok(Body::dangerous_pre_encoded(
+        encoded,
+        ContentTransferEncoding::Base64,
+    ))
+}
+
+impl TryFrom<Attachment> for SinglePart {
+    type Error = lettre::error::Error;
 
-        lettre::message::Attachment::new(attachment.name().clone())
-            .body(attachment.data().clone(), content_type)
+    fn try_from(attachment: Attachment) -> std::result::Result<Self, Self::Error> {
+        attachment_part(&attachment, &mut base64_body)
     }
 }
 
+/// The MIME part of `attachment`, with the body made by `attachment_body`.
+fn attachment_part(
+    attachment: &Attachment,
+    attachment_body: &mut impl FnMut(&AttachmentData) -> std::io::Result<Body>,
+) -> Result<SinglePart, lettre::error::Error> {
+    let content_type =
+        ContentType::parse(attachment.mime_type()).unwrap_or_else(|_| ContentType::octet_stream());
+
+    let part = match attachment.content_id() {
+        Some(content_id) => lettre::message::Attachment::new_inline_with_name(
+            content_id.clone(),
+            attachment.name().clone(),
+        ),
+        None => lettre::message::Attachment::new(attachment.name().clone()),
+    };
+    Ok(part.body(attachment_body(attachment.data())?, content_type))
+}
+
 impl From<EmailAddress> for lettre::Address {
     fn from(address: EmailAddress) -> Self {
         (*address).clone()
@@ -97,51 +359,127 @@ impl TryFrom<EmailWithSender> for Message {
     type Error = lettre::error::Error;
 
     fn try_from(email_with_sender: EmailWithSender) -> std::result::Result<Self, Self::Error> {
-        let sender = email_with_sender.sender();
-        let email = email_with_sender.email();
-        let mut builder = Message::builder()
-            .from(Mailbox::new(
-                Some(sender.name().clone()),
-                sender.email().clone().into(),
-            ))
-            .subject(email.subject().clone());
+        build_message(&email_with_sender, &mut base64_body)
+    }
+}
 
-        if let Some(reply_to) = email.reply_to() {
-            builder = builder.reply_to(Mailbox::new(
-                Some(reply_to.name().clone()),
-                reply_to.email().clone().into(),
-            ));
-        }
+/// Builds the message of `email_with_sender`, with the bodies of attachments
+/// made by `attachment_body`.
+fn build_message(
+    email_with_sender: &EmailWithSender,
+    attachment_body: &mut impl FnMut(&AttachmentData) -> std::io::Result<Body>,
+) -> Result<Message, lettre::error::Error> {
+    let sender = email_with_sender.sender();
+    let email = email_with_sender.email();
+    let mut builder = Message::builder()
+        .from(Mailbox::new(
+            Some(sender.name().clone()),
+            sender.email().clone().into(),
+        ))
+        .subject(email.subject().clone());
 
-        for recipient in email.public_recipients() {
-            builder = builder.to(Mailbox::new(None, recipient.clone().into()));
-        }
+    if let Some(message_id) = email.message_id() {
+        builder = builder.message_id(Some(format!("<{message_id}>")));
+    }
 
-        for recipient in email.cc_recipients() {
-            builder = builder.cc(Mailbox::new(None, recipient.clone().into()));
-        }
+    if let Some(reply_to) = email.reply_to() {
+        builder = builder.reply_to(Mailbox::new(
+            Some(reply_to.name().clone()),
+            reply_to.email().clone().into(),
+        ));
+    }
 
-        for recipient in email.bcc_recipients() {
-            builder = builder.bcc(Mailbox::new(None, recipient.clone().into()));
-        }
+    for recipient in email.public_recipients() {
+        builder = builder.to(recipient.clone().into());
+    }
+
+    for recipient in email.cc_recipients() {
+        builder = builder.cc(recipient.clone().into());
+    }
+
+    for recipient in email.bcc_recipients() {
+        builder = builder.bcc(recipient.clone().into());
+    }
 
-        let attachments = email.attachments().clone();
-        if attachments.is_empty() {
-            builder.body(email.body())
+    let attachments = email.attachments();
+    let (text, html) = email.email_body().into_parts();
+    let Some(html) = html else {
+        return if attachments.is_empty() {
+            builder.body(text.unwrap_or_default())
         } else {
             let mut multipart = MultiPart::mixed()
-                .singlepart(SinglePart::plain(email.body()))
+                .singlepart(SinglePart::plain(text.unwrap_or_default()))
                 .singlepart(SinglePart::plain("\n".to_owned()));
 
             for attachment in attachments {
-                multipart = multipart.singlepart(attachment.into());
+                multipart = multipart.singlepart(attachment_part(attachment, attachment_body)?);
             }
 
             builder.multipart(multipart)
+        };
+    };
+
+    let (inline_attachments, attachments): (Vec<_>, Vec<_>) = attachments
+        .iter()
+        .partition(|attachment| attachment.is_inline());
+    let body = html_body_part(text, html, inline_attachments, attachment_body)?;
+    if attachments.is_empty() {
+        return match body {
+            BodyPart::Single(part) => builder.singlepart(part),
+            BodyPart::Multi(part) => builder.multipart(part),
+        };
+    }
+
+    let mut multipart = body.append_to(MultiPart::mixed().build());
+
+    for attachment in attachments {
+        multipart = multipart.singlepart(attachment_part(attachment, attachment_body)?);
+    }
+
+    builder.multipart(multipart)
+}
+
+/// The MIME part holding the body of an email.
+enum BodyPart {
+    Single(SinglePart),
+    Multi(MultiPart),
+}
+
+impl BodyPart {
+    fn append_to(self, multipart: MultiPart) -> MultiPart {
+        match self {
+            BodyPart::Single(part) => multipart.singlepart(part),
+            BodyPart::Multi(part) => multipart.multipart(part),
         }
     }
 }
 
+/// An HTML body, wrapped in `multipart/related` together with any inline
+/// attachments it references by Content-ID, and in `multipart/alternative`
+/// if there is a plain-text fallback.
+fn html_body_part(
+    text: Option<String>,
+    html: String,
+    inline_attachments: Vec<&Attachment>,
+    attachment_body: &mut impl FnMut(&AttachmentData) -> std::io::Result<Body>,
+) -> Result<BodyPart, lettre::error::Error> {
+    let html_part = if inline_attachments.is_empty() {
+        BodyPart::Single(SinglePart::html(html))
+    } else {
+        let mut related = MultiPart::related().singlepart(SinglePart::html(html));
+        for attachment in inline_attachments {
+            related = related.singlepart(attachment_part(attachment, attachment_body)?);
+        }
+        BodyPart::Multi(related)
+    };
+    Ok(match text {
+        Some(text) => BodyPart::Multi(
+            html_part.append_to(MultiPart::alternative().singlepart(SinglePart::plain(text))),
+        ),
+        None => html_part,
+    })
+}
+
 impl From<EmailCredentials> for Credentials {
     fn from(credentials: EmailCredentials) -> Self {
         Credentials::new(
@@ -154,12 +492,286 @@ impl From<EmailCredentials> for Credentials {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::Attachment;
+    use crate::{Attachment, ParsedEml, Recipient};
+    use indexmap::IndexSet;
+    use std::io::Read;
 
     #[test]
     fn pdf_content_type_is_applied() {
-        let single_part: SinglePart = Attachment::pdf("a.pdf", vec![1, 2, 3]).into();
+        let single_part = SinglePart::try_from(Attachment::pdf("a.pdf", vec![1, 2, 3])).unwrap();
         let formatted = String::from_utf8(single_part.formatted()).expect("utf8 email headers");
         assert!(formatted.contains("application/pdf"));
     }
+
+    #[test]
+    fn base64_body_matches_lettre_across_chunks() {
+        let content = (0..57 * 1024 * 2 + 100)
+            .map(|i| (i % 251) as u8)
+            .collect::<Vec<_>>();
+        let expected = Body::new(content.clone());
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("report.bin");
+        std::fs::write(&path, &content).unwrap();
+
+        for data in [AttachmentData::from(content), AttachmentData::file(path)] {
+            let body = base64_body(&data).unwrap();
+            assert_eq!(body.encoding(), ContentTransferEncoding::Base64);
+            assert_eq!(body.as_ref(), expected.as_ref());
+        }
+    }
+
+    #[test]
+    fn missing_attachment_file_fails_to_compose() {
+        let email = Email::builder()
+            .public_recipients(IndexSet::from([Recipient::sample_bob()]))
+            .attachments(IndexSet::from([Attachment::pdf(
+                "report.pdf",
+                AttachmentData::file("/nonexistent/report.pdf"),
+            )]))
+            .build();
+        assert!(matches!(
+            compose_message(email, EmailAccount::sample()),
+            Err(SendEmailError::CreateEmail(lettre::error::Error::Io(_)))
+        ));
+    }
+
+    #[test]
+    fn formatted_message_encodes_attachments_when_written() {
+        let content = (0..100_000).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("report.bin");
+        std::fs::write(&path, &content).unwrap();
+        let email = Email::builder()
+            .public_recipients(IndexSet::from([Recipient::sample_bob()]))
+            .html_body(r#"<img src="cid:logo">"#.to_owned())
+            .attachments(IndexSet::from([
+                Attachment::pdf("report.pdf", AttachmentData::file(&path)),
+                Attachment::sample_inline(),
+            ]))
+            .build();
+
+        let message = format_message(email.clone(), EmailAccount::sample()).unwrap();
+        let eml = message.to_bytes().unwrap();
+        assert_eq!(message.size(), eml.len() as u64);
+        assert!(!String::from_utf8_lossy(&eml).contains("mejla-attachment-"));
+        let parsed = ParsedEml::parse(&eml).unwrap();
+        let contents = |email: &Email| {
+            email
+                .attachments()
+                .iter()
+                .map(|attachment| {
+                    let mut content = Vec::new();
+                    attachment
+                        .data()
+                        .open()
+                        .unwrap()
+                        .read_to_end(&mut content)
+                        .unwrap();
+                    (attachment.name().clone(), content)
+                })
+                .collect::<IndexSet<_>>()
+        };
+        assert_eq!(contents(parsed.email()), contents(&email));
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            format_message(email, EmailAccount::sample()),
+            Err(SendEmailError::CreateEmail(lettre::error::Error::Io(_)))
+        ));
+    }
+
+    fn formatted(email: Email) -> String {
+        let message = compose_message(email, EmailAccount::sample()).unwrap();
+        String::from_utf8(message.formatted()).unwrap()
+    }
+
+    #[test]
+    fn write_eml() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("invoice.eml");
+        let email_with_sender = EmailWithSender::builder()
+            .email(Email::sample())
+            .sender(EmailAccount::sample())
+            .build();
+        email_with_sender.write_eml(&path).unwrap();
+
+        let eml = std::fs::read_to_string(&path).unwrap();
+        assert!(eml.contains("From: \"Alice Smith\" <alice@example.com>\r\n"));
+        assert!(eml.contains("Subject: Sample Email Subject\r\n"));
+        assert!(eml.contains("Content-Disposition: attachment; filename=\"sample.pdf\""));
+    }
+
+    #[test]
+    fn dry_run_writes_eml_without_smtp() {
+        let directory = tempfile::tempdir().unwrap();
+        let mode = DeliveryMode::dry_run(directory.path());
+        let sent = send_email_with_delivery_mode_archived(
+            Email::sample(),
+            EmailCredentials::sample(),
+            &mode,
+        )
+        .unwrap();
+        let files = std::fs::read_dir(directory.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect::<Vec<_>>();
+        assert_eq!(files.len(), 1);
+        // The archived message is the written one, not composed anew.
+        assert_eq!(std::fs::read(&files[0]).unwrap(), sent.to_eml().unwrap());
+        assert_eq!(
+            sent.response().message().collect::<Vec<_>>(),
+            [files[0].display().to_string()]
+        );
+
+        let credentials = EmailCredentials::builder()
+            .smtp_server(
+                SmtpServer::builder()
+                    .host("smtp.example.com")
+                    .port(465)
+                    .max_message_size(100)
+                    .build(),
+            )
+            .account(EmailAccount::sample())
+            .password("open sesame".into())
+            .build();
+        assert!(matches!(
+            send_email_with_delivery_mode(Email::sample(), credentials, &mode),
+            Err(SendEmailError::Policy(
+                SendPolicyViolation::MessageTooLarge { limit: 100, .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn message_id_is_applied() {
+        let email = Email::builder()
+            .public_recipients(IndexSet::from([Recipient::sample_bob()]))
+            .message_id("abc.123@example.com".to_owned())
+            .build();
+        assert!(formatted(email).contains("Message-ID: <abc.123@example.com>"));
+    }
+
+    #[test]
+    fn html_body_with_text_is_multipart_alternative() {
+        let email = Email::builder()
+            .public_recipients(IndexSet::from([Recipient::sample_bob()]))
+            .subject("Invoice".to_owned())
+            .body("Plain invoice".to_owned())
+            .html_body("<p>HTML invoice</p>".to_owned())
+            .build();
+        let formatted = formatted(email);
+        assert!(formatted.contains("multipart/alternative"));
+        assert!(!formatted.contains("multipart/mixed"));
+        assert!(formatted.contains("Plain invoice"));
+        assert!(formatted.contains("<p>HTML invoice</p>"));
+    }
+
+    #[test]
+    fn html_only_body_is_single_html_part() {
+        let email = Email::builder()
+            .public_recipients(IndexSet::from([Recipient::sample_bob()]))
+            .subject("Invoice".to_owned())
+            .html_body("<p>HTML invoice</p>".to_owned())
+            .build();
+        let formatted = formatted(email);
+        assert!(formatted.contains("Content-Type: text/html"));
+        assert!(!formatted.contains("multipart"));
+    }
+
+    #[test]
+    fn html_body_with_attachments_nests_alternative_in_mixed() {
+        let email = Email::builder()
+            .public_recipients(IndexSet::from([Recipient::sample_bob()]))
+            .subject("Invoice".to_owned())
+            .body("Plain invoice".to_owned())
+            .html_body("<p>HTML invoice</p>".to_owned())
+            .attachments(IndexSet::from([Attachment::sample()]))
+            .build();
+        let formatted = formatted(email);
+        let mixed = formatted.find("multipart/mixed").unwrap();
+        let alternative = formatted.find("multipart/alternative").unwrap();
+        assert!(mixed < alternative);
+        assert!(formatted.contains("application/pdf"));
+    }
+
+    #[test]
+    fn inline_attachments_are_related_to_html_body() {
+        let email = Email::builder()
+            .public_recipients(IndexSet::from([Recipient::sample_bob()]))
+            .subject("Invoice".to_owned())
+            .body("Plain invoice".to_owned())
+            .html_body(r#"<img src="cid:logo">"#.to_owned())
+            .attachments(IndexSet::from([
+                Attachment::sample_inline(),
+                Attachment::sample(),
+            ]))
+            .build();
+        let formatted = formatted(email);
+        let mixed = formatted.find("multipart/mixed").unwrap();
+        let alternative = formatted.find("multipart/alternative").unwrap();
+        let related = formatted.find("multipart/related").unwrap();
+        let logo = formatted.find("Content-ID: <logo>").unwrap();
+        let pdf = formatted.find("application/pdf").unwrap();
+        assert!(mixed < alternative && alternative < related && related < logo);
+        assert!(logo < pdf);
+        assert!(formatted.contains("Content-Disposition: inline"));
+    }
+
+    #[test]
+    fn inline_attachments_without_html_body_are_attached() {
+        let email = Email::builder()
+            .public_recipients(IndexSet::from([Recipient::sample_bob()]))
+            .subject("Invoice".to_owned())
+            .body("Plain invoice".to_owned())
+            .attachments(IndexSet::from([Attachment::sample_inline()]))
+            .build();
+        let formatted = formatted(email);
+        assert!(formatted.contains("multipart/mixed"));
+        assert!(!formatted.contains("multipart/related"));
+        assert!(formatted.contains("Content-ID: <logo>"));
+    }
+
+    fn unreachable_credentials() -> EmailCredentials {
+        // A port that was free a moment ago, so nothing is listening on it.
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        EmailCredentials::builder()
+            .smtp_server(
+                SmtpServer::builder()
+                    .host("127.0.0.1")
+                    .port(port)
+                    .tls(TlsMode::None)
+                    .timeout(std::time::Duration::from_secs(5))
+                    .build(),
+            )
+            .account(EmailAccount::sample_alice())
+            .password("open sesame".into())
+            .build()
+    }
+
+    #[tokio::test]
+    async fn async_send_to_unreachable_server_fails() {
+        let result =
+            send_email_with_credentials_async(Email::sample(), unreachable_credentials()).await;
+        assert!(matches!(result, Err(SendEmailError::SendEmail(_))));
+    }
+
+    #[tokio::test]
+    async fn async_transport_checks_its_policy_before_connecting() {
+        let transport = AsyncSmtpMailTransport::try_from(unreachable_credentials())
+            .unwrap()
+            .with_send_policy(SendPolicy::builder().max_message_size(100).build());
+        let result =
+            send_email_with_async_transport(Email::sample(), EmailAccount::sample(), &transport)
+                .await;
+        assert!(matches!(
+            result,
+            Err(SendEmailError::Policy(
+                SendPolicyViolation::MessageTooLarge { limit: 100, .. }
+            ))
+        ));
+    }
 }
@@ -0,0 +1,38 @@
+use lettre::transport::smtp::response::{Category, Code, Detail, Response, Severity};
+
+use crate::{FormattedMessage, SendEmailError, SendPolicy};
+
+/// A destination that composed email messages can be delivered to, e.g. an
+/// SMTP server, a directory of `.eml` files or an in-memory inbox for tests.
+///
+/// The trait is blocking. Asynchronous sending is only offered for SMTP, by
+/// [`crate::AsyncSmtpMailTransport`]: the other transports write to
+/// local files or memory and gain nothing from being async, and an async variant
+/// of the trait would tie every implementation to an executor.
+pub trait MailTransport {
+    /// Delivers the `message`, returning the (possibly synthesized) SMTP response.
+    fn send_message(&self, message: &FormattedMessage) -> Result<Response, SendEmailError>;
+
+    /// The checks done before a message is composed and sent with this
+    /// transport, see [`crate::send_email_with_transport`].
+    fn send_policy(&self) -> SendPolicy {
+        SendPolicy::default()
+    }
+}
+
+/// A `250 OK` response, used by transports that do not talk to an SMTP server.
+pub(crate) fn ok_response() -> Response {
+    ok_response_with("OK")
+}
+
+/// A `250` response with `message`, used by transports that do not talk to an SMTP server.
+pub(crate) fn ok_response_with(message: impl Into<String>) -> Response {
+    Response::new(
+        Code::new(
+            Severity::PositiveCompletion,
+            Category::MailSystem,
+            Detail::Zero,
+        ),
+        vec![message.into()],
+    )
+}
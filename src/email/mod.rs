@@ -1,24 +1,82 @@
+mod async_smtp_mail_transport;
 mod attachment;
+mod attachment_data;
+mod attachment_error;
+mod delivery_mode;
 #[allow(clippy::module_inception)]
 mod email;
 mod email_account;
 mod email_address;
+mod email_body;
 mod email_credentials;
+mod email_profiles;
 mod email_settings;
 mod email_settings_selector;
+mod eml_parse_error;
+mod file_mail_transport;
+mod formatted_message;
+mod in_memory_mail_transport;
 mod lettre_bridge;
+mod mail_transport;
+mod parsed_eml;
+mod recipient;
+mod send_policy;
+mod send_policy_violation;
+mod sent_email;
+mod smtp_autoconfig;
+mod smtp_mail_transport;
+mod smtp_preset;
 mod smtp_server;
+mod stub_mail_transport;
 mod template;
+mod template_builtins;
+mod template_error;
+mod template_filter;
 mod template_part;
+mod template_placeholder;
+mod template_syntax;
+mod template_value;
+mod template_variables;
+mod tls_mode;
+mod unsupported_eml_construct;
 
+pub use async_smtp_mail_transport::*;
 pub use attachment::*;
+pub use attachment_data::*;
+pub use attachment_error::*;
+pub use delivery_mode::*;
 pub use email::*;
 pub use email_account::*;
 pub use email_address::*;
+pub use email_body::*;
 pub use email_credentials::*;
+pub use email_profiles::*;
 pub use email_settings::*;
 pub use email_settings_selector::*;
+pub use eml_parse_error::*;
+pub use file_mail_transport::*;
+pub use formatted_message::*;
+pub use in_memory_mail_transport::*;
 pub use lettre_bridge::*;
+pub use mail_transport::*;
+pub use parsed_eml::*;
+pub use recipient::*;
+pub use send_policy::*;
+pub use send_policy_violation::*;
+pub use sent_email::*;
+pub use smtp_autoconfig::*;
+pub use smtp_mail_transport::*;
+pub use smtp_preset::*;
 pub use smtp_server::*;
+pub use stub_mail_transport::*;
 pub use template::*;
+pub use template_builtins::*;
+pub use template_error::*;
+pub(crate) use template_filter::*;
 pub use template_part::*;
+pub use template_placeholder::*;
+pub(crate) use template_syntax::*;
+pub use template_value::*;
+pub use template_variables::*;
+pub use tls_mode::*;
+pub use unsupported_eml_construct::*;
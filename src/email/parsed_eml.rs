@@ -0,0 +1,407 @@
+use std::path::Path;
+
+use getset::Getters;
+use indexmap::IndexSet;
+use mail_parser::{Address, MessageParser, MessagePart, MimeHeaders, PartType};
+
+use crate::{
+    Attachment, Email, EmailAccount, EmailAddress, EmailWithSender, EmlParseError, Recipient,
+    UnsupportedEmlConstruct,
+};
+
+/// An [`Email`] parsed from a raw RFC 5322 message, e.g. an `.eml` file
+/// written by [`EmailWithSender::write_eml`], together with its sender.
+///
+/// The first `text/plain` and `text/html` parts which are not attachments
+/// become the bodies and every other part becomes an [`Attachment`], inline
+/// if it has a `Content-ID`. Anything [`Email`] cannot represent is listed in
+/// [`ParsedEml::unsupported`] rather than silently dropped, except the `Date`
+/// and MIME headers, which are generated anew when the email is sent.
+#[derive(Debug, Clone, PartialEq, Getters)]
+pub struct ParsedEml {
+    #[getset(get = "pub")]
+    email: Email,
+
+    /// The first `From` address, with an empty name if it has no display
+    /// name, `None` if there is none.
+    #[getset(get = "pub")]
+    sender: Option<EmailAccount>,
+
+    #[getset(get = "pub")]
+    unsupported: Vec<UnsupportedEmlConstruct>,
+}
+
+impl ParsedEml {
+    /// Headers of the message that are read into [`Email`], or regenerated
+    /// when it is sent, compared case-insensitively.
+    const SUPPORTED_HEADERS: &[&str] = &[
+        "From",
+        "To",
+        "Cc",
+        "Bcc",
+        "Reply-To",
+        "Subject",
+        "Message-ID",
+        "Date",
+        "MIME-Version",
+        "Content-Type",
+        "Content-Transfer-Encoding",
+    ];
+
+    /// Parses the raw message `eml`.
+    pub fn parse(eml: impl AsRef<[u8]>) -> Result<Self, EmlParseError> {
+        let message = MessageParser::default()
+            .parse(eml.as_ref())
+            .filter(|message| !message.headers().is_empty())
+            .ok_or(EmlParseError::NotAMessage)?;
+
+        let mut reader = EmlReader::default();
+        for header in message.headers() {
+            let name = header.name();
+            if !Self::SUPPORTED_HEADERS
+                .iter()
+                .any(|supported| supported.eq_ignore_ascii_case(name))
+            {
+                reader.unsupported(UnsupportedEmlConstruct::Header {
+                    name: name.to_owned(),
+                });
+            }
+        }
+
+        let sender = reader.single_account("From", message.from());
+        let reply_to = reader.single_account("Reply-To", message.reply_to());
+        let public_recipients = reader.recipients("To", message.to());
+        let cc_recipients = reader.recipients("Cc", message.cc());
+        let bcc_recipients = reader.recipients("Bcc", message.bcc());
+        reader.read_part(&message, 0);
+
+        let email = Email::builder()
+            .public_recipients(public_recipients)
+            .cc_recipients(cc_recipients)
+            .bcc_recipients(bcc_recipients)
+            .subject(message.subject().unwrap_or_default().to_owned())
+            .maybe_body(reader.text)
+            .maybe_html_body(reader.html)
+            .maybe_reply_to(reply_to)
+            .attachments(reader.attachments)
+            .maybe_message_id(message.message_id().map(str::to_owned))
+            .build();
+        Ok(Self {
+            email,
+            sender,
+            unsupported: reader.unsupported,
+        })
+    }
+
+    /// Reads and parses the `.eml` file at `path`.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, EmlParseError> {
+        let path = path.as_ref();
+        let eml = std::fs::read(path).map_err(|underlying| EmlParseError::Read {
+            path: path.to_owned(),
+            underlying,
+        })?;
+        Self::parse(eml)
+    }
+
+    /// Whether the message was parsed without dropping anything.
+    pub fn is_complete(&self) -> bool {
+        self.unsupported.is_empty()
+    }
+
+    /// The email with its sender, ready to be sent again, `None` if the
+    /// message has no sender.
+    pub fn into_email_with_sender(self) -> Option<EmailWithSender> {
+        let sender = self.sender?;
+        Some(
+            EmailWithSender::builder()
+                .email(self.email)
+                .sender(sender)
+                .build(),
+        )
+    }
+}
+
+/// Bodies, attachments and unsupported constructs collected while reading a message.
+#[derive(Default)]
+struct EmlReader {
+    text: Option<String>,
+    html: Option<String>,
+    attachments: IndexSet<Attachment>,
+    unsupported: Vec<UnsupportedEmlConstruct>,
+}
+
+impl EmlReader {
+    fn unsupported(&mut self, construct: UnsupportedEmlConstruct) {
+        self.unsupported.push(construct);
+    }
+
+    /// The valid addresses of `address`, reporting groups and invalid addresses.
+    fn addresses(
+        &mut self,
+        header: &str,
+        address: Option<&Address<'_>>,
+    ) -> Vec<(Option<String>, EmailAddress)> {
+        let Some(address) = address else {
+            return Vec::new();
+        };
+        if let Address::Group(groups) = address {
+            for group in groups {
+                self.unsupported(UnsupportedEmlConstruct::AddressGroup {
+                    header: header.to_owned(),
+                    name: group.name.as_deref().unwrap_or_default().to_owned(),
+                });
+            }
+        }
+
+        let mut addresses = Vec::new();
+        for addr in address.iter() {
+            let value = addr.address().or(addr.name()).unwrap_or_default();
+            match value.parse::<EmailAddress>() {
+                Ok(email) => addresses.push((addr.name().map(str::to_owned), email)),
+                Err(_) => self.unsupported(UnsupportedEmlConstruct::InvalidAddress {
+                    header: header.to_owned(),
+                    value: value.to_owned(),
+                }),
+            }
+        }
+        addresses
+    }
+
+    fn recipients(&mut self, header: &str, address: Option<&Address<'_>>) -> IndexSet<Recipient> {
+        self.addresses(header, address)
+            .into_iter()
+            .map(|(name, email)| Recipient::builder().maybe_name(name).email(email).build())
+            .collect()
+    }
+
+    /// The first address of `address`, reporting any further ones.
+    fn single_account(
+        &mut self,
+        header: &str,
+        address: Option<&Address<'_>>,
+    ) -> Option<EmailAccount> {
+        let mut addresses = self.addresses(header, address).into_iter();
+        let (name, email) = addresses.next()?;
+        for (_, extra) in addresses {
+            self.unsupported(UnsupportedEmlConstruct::ExtraAddress {
+                header: header.to_owned(),
+                value: extra.to_string(),
+            });
+        }
+        Some(
+            EmailAccount::builder()
+                .name(name.unwrap_or_default())
+                .email(email)
+                .build(),
+        )
+    }
+
+    fn read_part(&mut self, message: &mail_parser::Message<'_>, id: u32) {
+        let Some(part) = message.part(id) else {
+            return;
+        };
+        let is_attachment = part.attachment_name().is_some()
+            || part
+                .content_disposition()
+                .is_some_and(|disposition| disposition.is_attachment());
+        match &part.body {
+            PartType::Multipart(children) => {
+                let subtype = part
+                    .content_type()
+                    .and_then(|content_type| content_type.subtype())
+                    .unwrap_or_default();
+                if !["mixed", "alternative", "related"]
+                    .iter()
+                    .any(|supported| supported.eq_ignore_ascii_case(subtype))
+                {
+                    self.unsupported(UnsupportedEmlConstruct::MultipartSubtype {
+                        subtype: subtype.to_owned(),
+                    });
+                }
+                for child in children {
+                    self.read_part(message, *child);
+                }
+            }
+            PartType::Text(text) if !is_attachment && mime_type(part) == "text/plain" => {
+                self.read_body(text, "text/plain", |reader| &mut reader.text);
+            }
+            PartType::Html(html) if !is_attachment => {
+                self.read_body(html, "text/html", |reader| &mut reader.html);
+            }
+            _ => {
+                let attachment = attachment(part);
+                let name = attachment.name().clone();
+                if !self.attachments.insert(attachment) {
+                    self.unsupported(UnsupportedEmlConstruct::DuplicateAttachment { name });
+                }
+            }
+        }
+    }
+
+    /// Keeps `content` as the body unless there already is one, ignoring
+    /// blank extra bodies, e.g. the separator mejla adds before attachments.
+    fn read_body(
+        &mut self,
+        content: &str,
+        mime_type: &str,
+        body: impl FnOnce(&mut Self) -> &mut Option<String>,
+    ) {
+        let body = body(self);
+        if body.is_none() {
+            *body = Some(content.to_owned());
+        } else if !content.trim().is_empty() {
+            self.unsupported(UnsupportedEmlConstruct::ExtraBody {
+                mime_type: mime_type.to_owned(),
+            });
+        }
+    }
+}
+
+/// The MIME type of `part`, e.g. `"application/pdf"`.
+fn mime_type(part: &MessagePart<'_>) -> String {
+    match part.content_type() {
+        Some(content_type) => match content_type.subtype() {
+            Some(subtype) => format!("{}/{subtype}", content_type.ctype()),
+            None => content_type.ctype().to_owned(),
+        }
+        .to_ascii_lowercase(),
+        None if part.is_message() => "message/rfc822".to_owned(),
+        None => "text/plain".to_owned(),
+    }
+}
+
+fn attachment(part: &MessagePart<'_>) -> Attachment {
+    let name = Attachment::sanitize_file_name(part.attachment_name().unwrap_or_default());
+    let data = match &part.body {
+        PartType::Message(message) => message.raw_message().to_vec(),
+        _ => part.contents().to_vec(),
+    };
+    match part.content_id() {
+        Some(content_id) => Attachment::inline(
+            content_id.trim_matches(['<', '>']),
+            name,
+            mime_type(part),
+            data,
+        ),
+        None => Attachment::new(name, mime_type(part), data),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip(email: Email) -> ParsedEml {
+        let eml = EmailWithSender::builder()
+            .email(email)
+            .sender(EmailAccount::sample())
+            .build()
+            .to_eml()
+            .unwrap();
+        ParsedEml::parse(eml).unwrap()
+    }
+
+    #[test]
+    fn roundtrip_with_attachments() {
+        let email = Email::builder()
+            .public_recipients(IndexSet::from([Recipient::sample_bob()]))
+            .cc_recipients(IndexSet::from([Recipient::sample_carol()]))
+            .subject("Invoice 42".to_owned())
+            .body("Please find the invoice attached.".to_owned())
+            .reply_to(EmailAccount::sample_bob())
+            .attachments(IndexSet::from([
+                Attachment::sample(),
+                Attachment::sample_other(),
+            ]))
+            .message_id("abc.123@example.com".to_owned())
+            .build();
+        let parsed = roundtrip(email.clone());
+        assert_eq!(parsed.unsupported(), &[]);
+        assert_eq!(parsed.sender(), &Some(EmailAccount::sample()));
+        assert_eq!(parsed.email(), &email);
+    }
+
+    #[test]
+    fn roundtrip_html_with_inline_attachment() {
+        let email = Email::builder()
+            .public_recipients(IndexSet::from([Recipient::sample_bob()]))
+            .subject("Newsletter".to_owned())
+            .body("Plain newsletter".to_owned())
+            .html_body(r#"<p>Newsletter <img src="cid:logo"></p>"#.to_owned())
+            .attachments(IndexSet::from([
+                Attachment::sample_inline(),
+                Attachment::sample(),
+            ]))
+            .build();
+        let parsed = roundtrip(email.clone());
+        assert!(parsed.is_complete());
+        assert_eq!(parsed.email().email_body(), email.email_body());
+        assert_eq!(parsed.email().attachments(), email.attachments());
+    }
+
+    #[test]
+    fn reports_unsupported_constructs() {
+        let eml = concat!(
+            "From: Alice <alice@example.com>, Bob <bob@example.com>\r\n",
+            "To: Team: carol@example.com, not-an-address;\r\n",
+            "Subject: Re: Invoice\r\n",
+            "In-Reply-To: <abc@example.com>\r\n",
+            "Content-Type: multipart/signed; boundary=\"b\"\r\n",
+            "\r\n",
+            "--b\r\n",
+            "Content-Type: text/plain\r\n",
+            "\r\n",
+            "Paid.\r\n",
+            "--b\r\n",
+            "Content-Type: application/pgp-signature; name=\"signature.asc\"\r\n",
+            "\r\n",
+            "signature\r\n",
+            "--b\r\n",
+            "Content-Type: application/pgp-signature; name=\"signature.asc\"\r\n",
+            "\r\n",
+            "signature\r\n",
+            "--b--\r\n",
+        );
+        let parsed = ParsedEml::parse(eml).unwrap();
+        assert_eq!(parsed.email().body(), "Paid.");
+        assert_eq!(
+            parsed.email().public_recipients(),
+            &IndexSet::from([Recipient::sample_carol()])
+        );
+        assert_eq!(parsed.email().attachments().len(), 1);
+        assert_eq!(
+            parsed.unsupported(),
+            &[
+                UnsupportedEmlConstruct::Header {
+                    name: "In-Reply-To".to_owned()
+                },
+                UnsupportedEmlConstruct::ExtraAddress {
+                    header: "From".to_owned(),
+                    value: "bob@example.com".to_owned()
+                },
+                UnsupportedEmlConstruct::AddressGroup {
+                    header: "To".to_owned(),
+                    name: "Team".to_owned()
+                },
+                UnsupportedEmlConstruct::InvalidAddress {
+                    header: "To".to_owned(),
+                    value: "not-an-address".to_owned()
+                },
+                UnsupportedEmlConstruct::MultipartSubtype {
+                    subtype: "signed".to_owned()
+                },
+                UnsupportedEmlConstruct::DuplicateAttachment {
+                    name: "signature.asc".to_owned()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn not_a_message() {
+        assert!(matches!(
+            ParsedEml::parse(""),
+            Err(EmlParseError::NotAMessage)
+        ));
+    }
+}
@@ -0,0 +1,173 @@
+use std::str::FromStr;
+
+use bon::Builder;
+use getset::Getters;
+use serde_with::{DeserializeFromStr, SerializeDisplay};
+
+use crate::{EmailAccount, EmailAddress};
+
+/// A recipient mailbox: an email address with an optional display name,
+/// e.g. `Acme Accounts <ap@acme.com>` or just `ap@acme.com`.
+///
+/// Serialized as a string in the same form, so bare addresses stored by
+/// older versions deserialize as recipients without a name.
+///
+/// Equality and hashing consider only the address, so a set of recipients
+/// never holds the same mailbox twice under different display names.
+#[derive(Debug, Clone, Builder, Getters, SerializeDisplay, DeserializeFromStr)]
+pub struct Recipient {
+    #[getset(get = "pub")]
+    name: Option<String>,
+
+    #[getset(get = "pub")]
+    email: EmailAddress,
+}
+
+impl Recipient {
+    pub fn sample() -> Self {
+        Self::sample_alice()
+    }
+
+    pub fn sample_other() -> Self {
+        Self::sample_bob()
+    }
+
+    pub fn sample_alice() -> Self {
+        EmailAccount::sample_alice().into()
+    }
+
+    pub fn sample_bob() -> Self {
+        EmailAccount::sample_bob().into()
+    }
+
+    pub fn sample_carol() -> Self {
+        EmailAddress::sample_carol().into()
+    }
+
+    pub fn sample_dave() -> Self {
+        EmailAddress::sample_dave().into()
+    }
+
+    pub fn sample_erin() -> Self {
+        EmailAddress::sample_erin().into()
+    }
+}
+
+impl PartialEq for Recipient {
+    fn eq(&self, other: &Self) -> bool {
+        self.email == other.email
+    }
+}
+
+impl Eq for Recipient {}
+
+impl std::hash::Hash for Recipient {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.email.hash(state);
+    }
+}
+
+impl From<EmailAddress> for Recipient {
+    fn from(email: EmailAddress) -> Self {
+        Self { name: None, email }
+    }
+}
+
+impl From<EmailAccount> for Recipient {
+    fn from(account: EmailAccount) -> Self {
+        Self {
+            name: Some(account.name().clone()),
+            email: account.email().clone(),
+        }
+    }
+}
+
+impl From<Recipient> for lettre::message::Mailbox {
+    fn from(recipient: Recipient) -> Self {
+        lettre::message::Mailbox::new(recipient.name, recipient.email.into())
+    }
+}
+
+impl From<lettre::message::Mailbox> for Recipient {
+    fn from(mailbox: lettre::message::Mailbox) -> Self {
+        Self {
+            name: mailbox.name,
+            email: mailbox.email.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for Recipient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        lettre::message::Mailbox::from(self.clone()).fmt(f)
+    }
+}
+
+impl FromStr for Recipient {
+    type Err = lettre::address::AddressError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        lettre::message::Mailbox::from_str(s).map(Self::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_with_name() {
+        let recipient = Recipient::builder()
+            .name("Acme Accounts".to_owned())
+            .email(EmailAddress::from_str("ap@acme.com").unwrap())
+            .build();
+        assert_eq!(recipient.to_string(), "Acme Accounts <ap@acme.com>");
+    }
+
+    #[test]
+    fn from_str_with_and_without_name() {
+        let named = Recipient::from_str("Acme Accounts <ap@acme.com>").unwrap();
+        assert_eq!(named.name().as_deref(), Some("Acme Accounts"));
+        assert_eq!(named.email().to_string(), "ap@acme.com");
+
+        let bare = Recipient::from_str("ap@acme.com").unwrap();
+        assert_eq!(bare.name(), &None);
+    }
+
+    #[test]
+    fn deserialize_legacy_bare_address() {
+        let recipients: Vec<Recipient> =
+            serde_json::from_str(r#"["alice@example.com", "Bob Johnson <bob@example.com>"]"#)
+                .unwrap();
+        assert_eq!(
+            recipients,
+            vec![
+                Recipient::from(EmailAddress::sample_alice()),
+                Recipient::sample_bob()
+            ]
+        );
+        assert_eq!(recipients[0].name(), &None);
+        assert_eq!(recipients[1].name().as_deref(), Some("Bob Johnson"));
+    }
+
+    #[test]
+    fn set_holds_an_address_once() {
+        let named = Recipient::sample_alice();
+        let bare = Recipient::from(named.email().clone());
+        assert_eq!(named, bare);
+
+        let recipients = [named, bare].into_iter().collect::<indexmap::IndexSet<_>>();
+        assert_eq!(recipients.len(), 1);
+        assert_eq!(
+            recipients.first().unwrap().name().as_deref(),
+            Some("Alice Smith")
+        );
+    }
+
+    #[test]
+    fn json_roundtrip() {
+        let recipient = Recipient::sample_alice();
+        let json = serde_json::to_string(&recipient).unwrap();
+        assert_eq!(serde_json::from_str::<Recipient>(&json).unwrap(), recipient);
+    }
+}
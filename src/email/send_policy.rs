@@ -0,0 +1,210 @@
+use crate::{
+    AttachmentData, Email, EmailAccount, FormattedMessage, SendEmailError, SendPolicyViolation,
+    SmtpServer, format_message,
+};
+use bon::Builder;
+use getset::{CopyGetters, Getters};
+use indexmap::IndexSet;
+
+/// Checks done before an email is sent, so that messages a provider would
+/// reject are not uploaded in vain.
+#[derive(Debug, Clone, PartialEq, Eq, Builder, Getters, CopyGetters)]
+pub struct SendPolicy {
+    /// Maximum size in bytes of the encoded message, i.e. including headers
+    /// and base64 encoded attachments, `None` means unlimited.
+    #[getset(get_copy = "pub")]
+    max_message_size: Option<u64>,
+
+    /// Lowercase attachment file extensions, without the dot, that are
+    /// rejected, none by default, see [`SendPolicy::with_default_blocked_extensions`].
+    #[builder(default)]
+    #[getset(get = "pub")]
+    blocked_extensions: IndexSet<String>,
+}
+
+impl Default for SendPolicy {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+impl SendPolicy {
+    /// Extensions of executable and script files, which most providers,
+    /// e.g. Gmail and Outlook, reject as attachments.
+    pub const DEFAULT_BLOCKED_EXTENSIONS: &[&str] = &[
+        "ade", "adp", "apk", "appx", "bat", "cab", "chm", "cmd", "com", "cpl", "dll", "dmg", "exe",
+        "hta", "ins", "isp", "jar", "js", "jse", "lib", "lnk", "mde", "msc", "msi", "msix", "msp",
+        "mst", "nsh", "pif", "ps1", "scr", "sct", "shb", "sys", "vb", "vbe", "vbs", "vxd", "wsc",
+        "wsf", "wsh",
+    ];
+
+    /// A policy allowing every message, which is the default.
+    pub fn unrestricted() -> Self {
+        Self::default()
+    }
+
+    /// This policy also rejecting attachments with one of the
+    /// [`SendPolicy::DEFAULT_BLOCKED_EXTENSIONS`].
+    pub fn with_default_blocked_extensions(mut self) -> Self {
+        self.blocked_extensions.extend(
+            Self::DEFAULT_BLOCKED_EXTENSIONS
+                .iter()
+                .map(|extension| (*extension).to_owned()),
+        );
+        self
+    }
+
+    /// A policy with the message size limit of `smtp_server`, see
+    /// [`SmtpServer::effective_max_message_size`], which blocks no extensions.
+    pub fn for_smtp_server(smtp_server: &SmtpServer) -> Self {
+        Self::builder()
+            .maybe_max_message_size(smtp_server.effective_max_message_size())
+            .build()
+    }
+
+    /// Checks the attachments of `email`, which is cheap and done before the
+    /// message is composed: their extensions, and their encoded size, predicted
+    /// from their sizes, against the size limit.
+    ///
+    /// Attachments whose size cannot be read are skipped, composing the
+    /// message then fails.
+    pub fn check_email(&self, email: &Email) -> Result<(), SendPolicyViolation> {
+        if let Some(limit) = self.max_message_size {
+            let size = email
+                .attachments()
+                .iter()
+                .filter_map(|attachment| attachment.data().size().ok())
+                .map(AttachmentData::base64_size)
+                .sum();
+            if size > limit {
+                return Err(SendPolicyViolation::AttachmentsTooLarge { size, limit });
+            }
+        }
+        for attachment in email.attachments() {
+            let Some((_, extension)) = attachment.name().rsplit_once('.') else {
+                continue;
+            };
+            let extension = extension.to_ascii_lowercase();
+            if self.blocked_extensions.contains(&extension) {
+                return Err(SendPolicyViolation::BlockedAttachment {
+                    name: attachment.name().clone(),
+                    extension,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the formatted `message`, returning its encoded size in bytes.
+    pub fn check_message(&self, message: &FormattedMessage) -> Result<u64, SendPolicyViolation> {
+        let size = message.size();
+        match self.max_message_size {
+            Some(limit) if size > limit => {
+                Err(SendPolicyViolation::MessageTooLarge { size, limit })
+            }
+            _ => Ok(size),
+        }
+    }
+
+    /// Composes `email` from `sender` and checks it without sending it,
+    /// returning the encoded size of the message in bytes.
+    pub fn validate(&self, email: Email, sender: EmailAccount) -> Result<u64, SendEmailError> {
+        self.check_email(&email).map_err(SendEmailError::Policy)?;
+        let message = format_message(email, sender)?;
+        self.check_message(&message).map_err(SendEmailError::Policy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Attachment, Recipient};
+
+    fn email_with(attachment: Attachment) -> Email {
+        Email::builder()
+            .public_recipients(IndexSet::from([Recipient::sample_bob()]))
+            .subject("Report".to_owned())
+            .attachments(IndexSet::from([attachment]))
+            .build()
+    }
+
+    #[test]
+    fn blocked_extension() {
+        let email = email_with(Attachment::new(
+            "setup.EXE",
+            "application/octet-stream",
+            vec![0],
+        ));
+        let policy = SendPolicy::default().with_default_blocked_extensions();
+        assert_eq!(
+            policy.check_email(&email),
+            Err(SendPolicyViolation::BlockedAttachment {
+                name: "setup.EXE".to_owned(),
+                extension: "exe".to_owned()
+            })
+        );
+        assert_eq!(policy.check_email(&Email::sample()), Ok(()));
+        assert_eq!(SendPolicy::default().check_email(&email), Ok(()));
+        assert_eq!(
+            SendPolicy::for_smtp_server(&SmtpServer::gmail()).check_email(&email),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn message_too_large() {
+        let email = email_with(Attachment::pdf("report.pdf", vec![0; 3000]));
+        let size = SendPolicy::default()
+            .validate(email.clone(), EmailAccount::sample())
+            .unwrap();
+        // Base64 encoding grows the attachment by a third.
+        assert!(size > 4000);
+
+        let policy = SendPolicy::builder().max_message_size(size - 1).build();
+        assert!(matches!(
+            policy.validate(email, EmailAccount::sample()),
+            Err(SendEmailError::Policy(SendPolicyViolation::MessageTooLarge { limit, .. }))
+                if limit == size - 1
+        ));
+    }
+
+    #[test]
+    fn attachments_too_large_before_composing() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("report.pdf");
+        std::fs::write(&path, vec![0; 3000]).unwrap();
+        let email = email_with(Attachment::pdf("report.pdf", AttachmentData::file(&path)));
+        let policy = SendPolicy::builder().max_message_size(3999).build();
+        assert_eq!(
+            policy.check_email(&email),
+            Err(SendPolicyViolation::AttachmentsTooLarge {
+                size: 4000 + 2 * 52,
+                limit: 3999
+            })
+        );
+        let policy = SendPolicy::builder().max_message_size(4104).build();
+        assert_eq!(policy.check_email(&email), Ok(()));
+    }
+
+    #[test]
+    fn for_smtp_server() {
+        assert_eq!(
+            SendPolicy::for_smtp_server(&SmtpServer::gmail()).max_message_size(),
+            Some(25_000_000)
+        );
+        let server = SmtpServer::builder()
+            .host("smtp.gmail.com")
+            .port(465)
+            .max_message_size(10_000_000)
+            .build();
+        assert_eq!(
+            SendPolicy::for_smtp_server(&server).max_message_size(),
+            Some(10_000_000)
+        );
+        assert_eq!(
+            SendPolicy::for_smtp_server(&SmtpServer::new("relay.internal", Default::default()))
+                .max_message_size(),
+            None
+        );
+    }
+}
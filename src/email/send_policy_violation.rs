@@ -0,0 +1,35 @@
+use std::fmt::{Display, Formatter};
+
+/// Why an email was rejected by a [`crate::SendPolicy`] before being sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SendPolicyViolation {
+    /// The encoded message is `size` bytes, more than the `limit` of the server.
+    MessageTooLarge { size: u64, limit: u64 },
+
+    /// The attachments alone are `size` bytes once encoded, more than the
+    /// `limit` of the server, found before the message was composed.
+    AttachmentsTooLarge { size: u64, limit: u64 },
+
+    /// The attachment named `name` has the blocked `extension`.
+    BlockedAttachment { name: String, extension: String },
+}
+
+impl Display for SendPolicyViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MessageTooLarge { size, limit } => write!(
+                f,
+                "message is {size} bytes encoded, more than the limit of {limit} bytes"
+            ),
+            Self::AttachmentsTooLarge { size, limit } => write!(
+                f,
+                "attachments are {size} bytes encoded, more than the limit of {limit} bytes"
+            ),
+            Self::BlockedAttachment { name, extension } => {
+                write!(f, "attachment {name} has blocked extension .{extension}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SendPolicyViolation {}
@@ -0,0 +1,46 @@
+use std::path::Path;
+
+use getset::Getters;
+use lettre::transport::smtp::response::Response;
+
+use crate::{FormattedMessage, SendEmailError};
+
+/// The outcome of sending an email: the response of the transport and the
+/// message exactly as it was sent, e.g. to archive it as an `.eml` file, see
+/// [`crate::send_email_with_transport_archived`].
+///
+/// The message refers to the content of its attachments rather than holding
+/// it, see [`FormattedMessage`], so archiving it reads file-backed
+/// attachments again.
+#[derive(Debug, Clone, PartialEq, Eq, Getters)]
+pub struct SentEmail {
+    #[getset(get = "pub")]
+    response: Response,
+
+    #[getset(get = "pub")]
+    message: FormattedMessage,
+}
+
+impl SentEmail {
+    pub(crate) fn new(response: Response, message: FormattedMessage) -> Self {
+        Self { response, message }
+    }
+
+    pub fn into_response(self) -> Response {
+        self.response
+    }
+
+    /// The sent message as the content of an `.eml` file, with the same
+    /// `Date` and `Message-ID` headers the recipients got.
+    pub fn to_eml(&self) -> Result<Vec<u8>, SendEmailError> {
+        self.message.encode()
+    }
+
+    /// Writes the sent message as an `.eml` file to `path`, see [`SentEmail::to_eml`].
+    ///
+    /// Attachments are encoded while the file is written, so they are never
+    /// held in memory.
+    pub fn write_eml(&self, path: impl AsRef<Path>) -> Result<(), SendEmailError> {
+        self.message.write_eml(path.as_ref())
+    }
+}
@@ -0,0 +1,107 @@
+use crate::{EmailAddress, SmtpPreset};
+
+/// Bundled, offline database of email domains and the [`SmtpPreset`] id of the
+/// provider hosting them, in the spirit of Mozilla's ISPDB.
+const DOMAINS: &[(&str, &str)] = &[
+    ("gmail.com", "gmail"),
+    ("googlemail.com", "gmail"),
+    ("outlook.com", "outlook"),
+    ("hotmail.com", "outlook"),
+    ("hotmail.co.uk", "outlook"),
+    ("hotmail.fr", "outlook"),
+    ("hotmail.de", "outlook"),
+    ("hotmail.it", "outlook"),
+    ("hotmail.es", "outlook"),
+    ("hotmail.se", "outlook"),
+    ("live.com", "outlook"),
+    ("live.co.uk", "outlook"),
+    ("live.se", "outlook"),
+    ("msn.com", "outlook"),
+    ("outlook.de", "outlook"),
+    ("outlook.fr", "outlook"),
+    ("outlook.se", "outlook"),
+    ("icloud.com", "icloud"),
+    ("me.com", "icloud"),
+    ("mac.com", "icloud"),
+    ("fastmail.com", "fastmail"),
+    ("fastmail.fm", "fastmail"),
+    ("fastmail.net", "fastmail"),
+    ("fastmail.org", "fastmail"),
+    ("messagingengine.com", "fastmail"),
+    ("yahoo.com", "yahoo"),
+    ("yahoo.co.uk", "yahoo"),
+    ("yahoo.co.jp", "yahoo"),
+    ("yahoo.de", "yahoo"),
+    ("yahoo.fr", "yahoo"),
+    ("yahoo.se", "yahoo"),
+    ("ymail.com", "yahoo"),
+    ("rocketmail.com", "yahoo"),
+    ("aol.com", "aol"),
+    ("aim.com", "aol"),
+    ("zoho.com", "zoho"),
+    ("zohomail.com", "zoho"),
+    ("zoho.eu", "zoho-eu"),
+    ("zohomail.eu", "zoho-eu"),
+    ("gmx.com", "gmx"),
+    ("gmx.net", "gmx"),
+    ("gmx.de", "gmx"),
+    ("gmx.at", "gmx"),
+    ("gmx.ch", "gmx"),
+    ("mailbox.org", "mailbox-org"),
+    ("proton.me", "proton-bridge"),
+    ("protonmail.com", "proton-bridge"),
+    ("protonmail.ch", "proton-bridge"),
+    ("pm.me", "proton-bridge"),
+];
+
+/// Resolves the SMTP provider of an email address from its domain, without
+/// any network access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SmtpAutoconfig;
+
+impl SmtpAutoconfig {
+    /// The preset of the provider hosting `domain`, e.g. `"gmail.com"`, if known.
+    pub fn preset_for_domain(domain: &str) -> Option<&'static SmtpPreset> {
+        let domain = domain.trim().trim_end_matches('.');
+        DOMAINS
+            .iter()
+            .find(|(known, _)| known.eq_ignore_ascii_case(domain))
+            .and_then(|(_, id)| SmtpPreset::by_id(id))
+    }
+
+    /// The preset of the provider hosting the domain of `email`, if known.
+    pub fn preset_for(email: &EmailAddress) -> Option<&'static SmtpPreset> {
+        Self::preset_for_domain(email.domain())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SmtpServer;
+    use std::str::FromStr;
+
+    #[test]
+    fn all_domains_resolve_to_a_preset() {
+        for (domain, id) in DOMAINS {
+            assert!(
+                SmtpPreset::by_id(id).is_some(),
+                "unknown preset {id} for {domain}"
+            );
+        }
+    }
+
+    #[test]
+    fn preset_for_email_address() {
+        let email = EmailAddress::from_str("jane@GoogleMail.com").unwrap();
+        assert_eq!(
+            SmtpAutoconfig::preset_for(&email).map(SmtpPreset::smtp_server),
+            Some(SmtpServer::gmail())
+        );
+    }
+
+    #[test]
+    fn unknown_domain() {
+        assert_eq!(SmtpAutoconfig::preset_for(&EmailAddress::sample()), None);
+    }
+}
@@ -0,0 +1,51 @@
+use lettre::{
+    SmtpTransport, Transport,
+    transport::smtp::{authentication::Credentials, response::Response},
+};
+
+use crate::{
+    EmailCredentials, FormattedMessage, MailTransport, SendEmailError, SendPolicy,
+    smtp_transport_builder,
+};
+
+/// Delivers messages to an SMTP server using lettre's blocking SMTP transport.
+///
+/// Messages are checked against the [`SendPolicy`] of the server before
+/// they are sent, see [`SendPolicy::for_smtp_server`].
+#[derive(Clone)]
+pub struct SmtpMailTransport {
+    transport: SmtpTransport,
+    policy: SendPolicy,
+}
+
+impl SmtpMailTransport {
+    /// Replaces the policy derived from the SMTP server.
+    pub fn with_send_policy(self, policy: SendPolicy) -> Self {
+        Self { policy, ..self }
+    }
+}
+
+impl TryFrom<EmailCredentials> for SmtpMailTransport {
+    type Error = SendEmailError;
+
+    fn try_from(credentials: EmailCredentials) -> Result<Self, Self::Error> {
+        let policy = SendPolicy::for_smtp_server(credentials.smtp_server());
+        let transport = smtp_transport_builder(credentials.smtp_server())
+            .map_err(SendEmailError::CreateSmtpTransport)?
+            .credentials(Credentials::from(credentials))
+            .build();
+        Ok(Self { transport, policy })
+    }
+}
+
+impl MailTransport for SmtpMailTransport {
+    fn send_message(&self, message: &FormattedMessage) -> Result<Response, SendEmailError> {
+        self.transport
+            .send_raw(message.envelope(), &message.encode()?)
+            .map_err(SendEmailError::SendEmail)
+    }
+
+    fn send_policy(&self) -> SendPolicy {
+        self.policy.clone()
+    }
+}
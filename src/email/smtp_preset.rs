@@ -0,0 +1,356 @@
+use getset::CopyGetters;
+
+use crate::{SmtpServer, TlsMode};
+
+/// A well-known SMTP provider with the host, port and TLS mode it expects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, CopyGetters, derive_more::Display)]
+#[display("{name}")]
+pub struct SmtpPreset {
+    /// Stable identifier, e.g. `"gmail"` or `"ses-eu-west-1"`.
+    #[getset(get_copy = "pub")]
+    id: &'static str,
+
+    /// Human readable name, e.g. `"Gmail"`.
+    #[getset(get_copy = "pub")]
+    name: &'static str,
+
+    #[getset(get_copy = "pub")]
+    host: &'static str,
+
+    #[getset(get_copy = "pub")]
+    port: u16,
+
+    #[getset(get_copy = "pub")]
+    tls: TlsMode,
+
+    /// The maximum size in bytes of an encoded message the provider accepts,
+    /// as documented by the provider, `None` if unknown.
+    #[getset(get_copy = "pub")]
+    max_message_size: Option<u64>,
+}
+
+/// One megabyte, as providers document their message size limits.
+const MB: u64 = 1_000_000;
+
+const fn preset(
+    id: &'static str,
+    name: &'static str,
+    host: &'static str,
+    port: u16,
+    tls: TlsMode,
+    max_message_size: Option<u64>,
+) -> SmtpPreset {
+    SmtpPreset {
+        id,
+        name,
+        host,
+        port,
+        tls,
+        max_message_size,
+    }
+}
+
+const fn amazon_ses(id: &'static str, name: &'static str, host: &'static str) -> SmtpPreset {
+    preset(id, name, host, 587, TlsMode::StartTls, Some(40 * MB))
+}
+
+impl SmtpPreset {
+    /// All presets, in the order they are presented to users.
+    ///
+    /// Proton Mail Bridge runs locally and serves a self-signed certificate
+    /// for STARTTLS, so sending with the `proton-bridge` preset only works once
+    /// that certificate, which the bridge can export, is trusted by the system.
+    pub const ALL: &'static [SmtpPreset] = &[
+        preset(
+            "gmail",
+            "Gmail",
+            "smtp.gmail.com",
+            465,
+            TlsMode::Implicit,
+            Some(25 * MB),
+        ),
+        preset(
+            "outlook",
+            "Outlook.com",
+            "smtp-mail.outlook.com",
+            587,
+            TlsMode::StartTls,
+            Some(20 * MB),
+        ),
+        preset(
+            "office365",
+            "Microsoft 365 / Office 365",
+            "smtp.office365.com",
+            587,
+            TlsMode::StartTls,
+            Some(35 * MB),
+        ),
+        preset(
+            "icloud",
+            "iCloud Mail",
+            "smtp.mail.me.com",
+            587,
+            TlsMode::StartTls,
+            Some(20 * MB),
+        ),
+        preset(
+            "fastmail",
+            "Fastmail",
+            "smtp.fastmail.com",
+            465,
+            TlsMode::Implicit,
+            Some(70 * MB),
+        ),
+        preset(
+            "yahoo",
+            "Yahoo Mail",
+            "smtp.mail.yahoo.com",
+            465,
+            TlsMode::Implicit,
+            Some(25 * MB),
+        ),
+        preset(
+            "aol",
+            "AOL Mail",
+            "smtp.aol.com",
+            465,
+            TlsMode::Implicit,
+            Some(25 * MB),
+        ),
+        preset(
+            "zoho",
+            "Zoho Mail",
+            "smtp.zoho.com",
+            465,
+            TlsMode::Implicit,
+            None,
+        ),
+        preset(
+            "zoho-eu",
+            "Zoho Mail (EU)",
+            "smtp.zoho.eu",
+            465,
+            TlsMode::Implicit,
+            None,
+        ),
+        preset("gmx", "GMX", "mail.gmx.com", 587, TlsMode::StartTls, None),
+        preset(
+            "mailbox-org",
+            "mailbox.org",
+            "smtp.mailbox.org",
+            465,
+            TlsMode::Implicit,
+            None,
+        ),
+        preset(
+            "proton-bridge",
+            "Proton Mail Bridge",
+            "127.0.0.1",
+            1025,
+            TlsMode::StartTls,
+            Some(25 * MB),
+        ),
+        preset(
+            "sendgrid",
+            "SendGrid",
+            "smtp.sendgrid.net",
+            587,
+            TlsMode::StartTls,
+            Some(30 * MB),
+        ),
+        preset(
+            "mailgun",
+            "Mailgun",
+            "smtp.mailgun.org",
+            587,
+            TlsMode::StartTls,
+            Some(25 * MB),
+        ),
+        preset(
+            "mailgun-eu",
+            "Mailgun (EU)",
+            "smtp.eu.mailgun.org",
+            587,
+            TlsMode::StartTls,
+            Some(25 * MB),
+        ),
+        preset(
+            "postmark",
+            "Postmark",
+            "smtp.postmarkapp.com",
+            587,
+            TlsMode::StartTls,
+            Some(10 * MB),
+        ),
+        preset(
+            "brevo",
+            "Brevo",
+            "smtp-relay.brevo.com",
+            587,
+            TlsMode::StartTls,
+            None,
+        ),
+        amazon_ses(
+            "ses-us-east-1",
+            "Amazon SES (US East, N. Virginia)",
+            "email-smtp.us-east-1.amazonaws.com",
+        ),
+        amazon_ses(
+            "ses-us-east-2",
+            "Amazon SES (US East, Ohio)",
+            "email-smtp.us-east-2.amazonaws.com",
+        ),
+        amazon_ses(
+            "ses-us-west-1",
+            "Amazon SES (US West, N. California)",
+            "email-smtp.us-west-1.amazonaws.com",
+        ),
+        amazon_ses(
+            "ses-us-west-2",
+            "Amazon SES (US West, Oregon)",
+            "email-smtp.us-west-2.amazonaws.com",
+        ),
+        amazon_ses(
+            "ses-ca-central-1",
+            "Amazon SES (Canada, Central)",
+            "email-smtp.ca-central-1.amazonaws.com",
+        ),
+        amazon_ses(
+            "ses-sa-east-1",
+            "Amazon SES (South America, São Paulo)",
+            "email-smtp.sa-east-1.amazonaws.com",
+        ),
+        amazon_ses(
+            "ses-eu-west-1",
+            "Amazon SES (Europe, Ireland)",
+            "email-smtp.eu-west-1.amazonaws.com",
+        ),
+        amazon_ses(
+            "ses-eu-west-2",
+            "Amazon SES (Europe, London)",
+            "email-smtp.eu-west-2.amazonaws.com",
+        ),
+        amazon_ses(
+            "ses-eu-west-3",
+            "Amazon SES (Europe, Paris)",
+            "email-smtp.eu-west-3.amazonaws.com",
+        ),
+        amazon_ses(
+            "ses-eu-central-1",
+            "Amazon SES (Europe, Frankfurt)",
+            "email-smtp.eu-central-1.amazonaws.com",
+        ),
+        amazon_ses(
+            "ses-eu-north-1",
+            "Amazon SES (Europe, Stockholm)",
+            "email-smtp.eu-north-1.amazonaws.com",
+        ),
+        amazon_ses(
+            "ses-eu-south-1",
+            "Amazon SES (Europe, Milan)",
+            "email-smtp.eu-south-1.amazonaws.com",
+        ),
+        amazon_ses(
+            "ses-ap-south-1",
+            "Amazon SES (Asia Pacific, Mumbai)",
+            "email-smtp.ap-south-1.amazonaws.com",
+        ),
+        amazon_ses(
+            "ses-ap-northeast-1",
+            "Amazon SES (Asia Pacific, Tokyo)",
+            "email-smtp.ap-northeast-1.amazonaws.com",
+        ),
+        amazon_ses(
+            "ses-ap-northeast-2",
+            "Amazon SES (Asia Pacific, Seoul)",
+            "email-smtp.ap-northeast-2.amazonaws.com",
+        ),
+        amazon_ses(
+            "ses-ap-northeast-3",
+            "Amazon SES (Asia Pacific, Osaka)",
+            "email-smtp.ap-northeast-3.amazonaws.com",
+        ),
+        amazon_ses(
+            "ses-ap-southeast-1",
+            "Amazon SES (Asia Pacific, Singapore)",
+            "email-smtp.ap-southeast-1.amazonaws.com",
+        ),
+        amazon_ses(
+            "ses-ap-southeast-2",
+            "Amazon SES (Asia Pacific, Sydney)",
+            "email-smtp.ap-southeast-2.amazonaws.com",
+        ),
+        amazon_ses(
+            "ses-me-south-1",
+            "Amazon SES (Middle East, Bahrain)",
+            "email-smtp.me-south-1.amazonaws.com",
+        ),
+        amazon_ses(
+            "ses-af-south-1",
+            "Amazon SES (Africa, Cape Town)",
+            "email-smtp.af-south-1.amazonaws.com",
+        ),
+        amazon_ses(
+            "ses-il-central-1",
+            "Amazon SES (Israel, Tel Aviv)",
+            "email-smtp.il-central-1.amazonaws.com",
+        ),
+    ];
+
+    /// The preset with identifier `id`, if any.
+    pub fn by_id(id: &str) -> Option<&'static SmtpPreset> {
+        Self::ALL.iter().find(|preset| preset.id == id)
+    }
+
+    /// The preset using the same host, port and TLS mode as `smtp_server`, if any.
+    pub fn matching(smtp_server: &SmtpServer) -> Option<&'static SmtpPreset> {
+        Self::ALL.iter().find(|preset| {
+            preset.host == smtp_server.host()
+                && preset.port == smtp_server.port()
+                && preset.tls == smtp_server.tls()
+        })
+    }
+
+    /// Server settings for this preset.
+    pub fn smtp_server(&self) -> SmtpServer {
+        SmtpServer::builder()
+            .host(self.host)
+            .port(self.port)
+            .tls(self.tls)
+            .build()
+    }
+}
+
+impl From<&SmtpPreset> for SmtpServer {
+    fn from(preset: &SmtpPreset) -> Self {
+        preset.smtp_server()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn ids_are_unique() {
+        let ids = SmtpPreset::ALL
+            .iter()
+            .map(SmtpPreset::id)
+            .collect::<HashSet<_>>();
+        assert_eq!(ids.len(), SmtpPreset::ALL.len());
+    }
+
+    #[test]
+    fn gmail_preset_matches_gmail_server() {
+        let gmail = SmtpPreset::by_id("gmail").unwrap();
+        assert_eq!(gmail.smtp_server(), SmtpServer::gmail());
+        assert_eq!(SmtpPreset::matching(&SmtpServer::gmail()), Some(gmail));
+    }
+
+    #[test]
+    fn matching_custom_server_is_none() {
+        let server = SmtpServer::new("smtp.example.com", TlsMode::StartTls);
+        assert_eq!(SmtpPreset::matching(&server), None);
+    }
+}
@@ -1,29 +1,308 @@
-use derive_more::AsRef;
-use serde::Deserialize;
-use serde::Serialize;
-
-#[derive(
-    Debug,
-    Clone,
-    PartialEq,
-    Eq,
-    Hash,
-    AsRef,
-    derive_more::Display,
-    derive_more::FromStr,
-    Serialize,
-    Deserialize,
-)]
-#[serde(transparent)]
-pub struct SmtpServer(String);
+use std::{
+    fmt::{Display, Formatter},
+    str::FromStr,
+    time::Duration,
+};
+
+use bon::Builder;
+use getset::{CopyGetters, Getters};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+
+use crate::{SmtpPreset, TlsMode};
+
+/// Connection settings for an SMTP server.
+///
+/// Deserializes both from the structured form and from the legacy plain
+/// hostname string (e.g. `"smtp.gmail.com"`), which means implicit TLS on port 465.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Builder, Getters, CopyGetters, Serialize)]
+pub struct SmtpServer {
+    /// Hostname of the SMTP server, e.g. `"smtp.gmail.com"`.
+    #[builder(into)]
+    #[getset(get = "pub")]
+    host: String,
+
+    #[getset(get_copy = "pub")]
+    port: u16,
+
+    #[builder(default)]
+    #[getset(get_copy = "pub")]
+    tls: TlsMode,
+
+    /// Timeout for connecting and for each SMTP command, `None` uses lettre's default.
+    #[serde_as(as = "Option<serde_with::DurationSeconds<u64>>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[getset(get_copy = "pub")]
+    timeout: Option<Duration>,
+
+    /// Maximum size in bytes of an encoded message the server accepts,
+    /// overriding the limit of the matching [`SmtpPreset`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[getset(get_copy = "pub")]
+    max_message_size: Option<u64>,
+}
 
 impl SmtpServer {
+    /// Creates a server config for `host` using the conventional port of `tls`.
+    pub fn new(host: impl Into<String>, tls: TlsMode) -> Self {
+        Self::builder()
+            .host(host)
+            .port(tls.default_port())
+            .tls(tls)
+            .build()
+    }
+
     pub fn gmail() -> Self {
-        SmtpServer("smtp.gmail.com".to_owned())
+        Self::new("smtp.gmail.com", TlsMode::Implicit)
+    }
+
+    /// The configured maximum message size, falling back to the limit of the
+    /// matching preset, if any.
+    pub fn effective_max_message_size(&self) -> Option<u64> {
+        self.max_message_size
+            .or_else(|| SmtpPreset::matching(self).and_then(SmtpPreset::max_message_size))
     }
 }
+
 impl Default for SmtpServer {
     fn default() -> Self {
         SmtpServer::gmail()
     }
 }
+
+/// Formats the server as `host:port`, bracketing IPv6 hosts (`[::1]:25`).
+///
+/// The TLS mode, timeout and size limit are not shown, so parsing the output
+/// back only restores the TLS mode conventionally used on the port; the serde
+/// form is the lossless representation.
+impl Display for SmtpServer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.host.contains(':') {
+            write!(f, "[{}]:{}", self.host, self.port)
+        } else {
+            write!(f, "{}:{}", self.host, self.port)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SmtpServerParseError {
+    EmptyHost,
+    /// A bracketed IPv6 host that is unterminated or followed by something other than `:port`.
+    InvalidHost(String),
+    InvalidPort(String),
+}
+
+impl Display for SmtpServerParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EmptyHost => write!(f, "SMTP server host cannot be empty"),
+            Self::InvalidHost(host) => write!(f, "invalid SMTP server host: {host}"),
+            Self::InvalidPort(port) => write!(f, "invalid SMTP server port: {port}"),
+        }
+    }
+}
+
+impl std::error::Error for SmtpServerParseError {}
+
+impl FromStr for SmtpServer {
+    type Err = SmtpServerParseError;
+
+    /// Parses `"host"` (implicit TLS on port 465) or `"host:port"`, in which
+    /// case the TLS mode is the one conventionally used on that port.
+    ///
+    /// IPv6 hosts are written in brackets when a port follows (`"[::1]:25"`);
+    /// a bare IPv6 address such as `"::1"` is taken as a host without a port.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (host, port) = split_host_port(s)?;
+        let port = port
+            .map(|port| {
+                port.parse::<u16>()
+                    .map_err(|_| SmtpServerParseError::InvalidPort(port.to_owned()))
+            })
+            .transpose()?;
+        if host.is_empty() {
+            return Err(SmtpServerParseError::EmptyHost);
+        }
+        Ok(match port {
+            Some(port) => Self::builder()
+                .host(host)
+                .port(port)
+                .tls(TlsMode::default_for_port(port))
+                .build(),
+            None => Self::new(host, TlsMode::Implicit),
+        })
+    }
+}
+
+/// Splits `s` into its host and optional port text, unwrapping bracketed IPv6 hosts.
+fn split_host_port(s: &str) -> Result<(&str, Option<&str>), SmtpServerParseError> {
+    if let Some(bracketed) = s.strip_prefix('[') {
+        let invalid_host = || SmtpServerParseError::InvalidHost(s.to_owned());
+        let (host, rest) = bracketed.split_once(']').ok_or_else(invalid_host)?;
+        return match rest {
+            "" => Ok((host, None)),
+            _ => rest
+                .strip_prefix(':')
+                .map(|port| (host, Some(port)))
+                .ok_or_else(invalid_host),
+        };
+    }
+    Ok(match s.rsplit_once(':') {
+        Some((host, _)) if host.contains(':') => (s, None),
+        Some((host, port)) => (host, Some(port)),
+        None => (s, None),
+    })
+}
+
+/// Serialized forms accepted when deserializing a [`SmtpServer`].
+#[serde_as]
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SmtpServerRepr {
+    /// Legacy form: just the hostname.
+    Host(String),
+    Structured {
+        host: String,
+        port: u16,
+        #[serde(default)]
+        tls: TlsMode,
+        #[serde_as(as = "Option<serde_with::DurationSeconds<u64>>")]
+        #[serde(default)]
+        timeout: Option<Duration>,
+        #[serde(default)]
+        max_message_size: Option<u64>,
+    },
+}
+
+impl TryFrom<SmtpServerRepr> for SmtpServer {
+    type Error = SmtpServerParseError;
+
+    fn try_from(repr: SmtpServerRepr) -> Result<Self, Self::Error> {
+        match repr {
+            SmtpServerRepr::Host(host) => host.parse(),
+            SmtpServerRepr::Structured {
+                host,
+                port,
+                tls,
+                timeout,
+                max_message_size,
+            } => Ok(Self::builder()
+                .host(host)
+                .port(port)
+                .tls(tls)
+                .maybe_timeout(timeout)
+                .maybe_max_message_size(max_message_size)
+                .build()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for SmtpServer {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        SmtpServerRepr::deserialize(deserializer)
+            .and_then(|repr| Self::try_from(repr).map_err(serde::de::Error::custom))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_legacy_plain_host() {
+        let server: SmtpServer = serde_json::from_str(r#""smtp.gmail.com""#).unwrap();
+        assert_eq!(server, SmtpServer::gmail());
+    }
+
+    #[test]
+    fn json_roundtrip_structured() {
+        let server = SmtpServer::builder()
+            .host("smtp.example.com")
+            .port(587)
+            .tls(TlsMode::StartTls)
+            .timeout(Duration::from_secs(30))
+            .build();
+        let json = serde_json::to_string(&server).unwrap();
+        assert_eq!(
+            json,
+            r#"{"host":"smtp.example.com","port":587,"tls":"starttls","timeout":30}"#
+        );
+        assert_eq!(serde_json::from_str::<SmtpServer>(&json).unwrap(), server);
+    }
+
+    #[test]
+    fn json_roundtrip_max_message_size() {
+        let server = SmtpServer::builder()
+            .host("smtp.example.com")
+            .port(465)
+            .max_message_size(10_000_000)
+            .build();
+        let json = serde_json::to_string(&server).unwrap();
+        assert_eq!(
+            json,
+            r#"{"host":"smtp.example.com","port":465,"tls":"implicit","max_message_size":10000000}"#
+        );
+        assert_eq!(serde_json::from_str::<SmtpServer>(&json).unwrap(), server);
+    }
+
+    #[test]
+    fn from_str_with_port_infers_tls() {
+        let server = SmtpServer::from_str("relay.internal:25").unwrap();
+        assert_eq!(server.host(), "relay.internal");
+        assert_eq!(server.port(), 25);
+        assert_eq!(server.tls(), TlsMode::Opportunistic);
+    }
+
+    #[test]
+    fn from_str_bracketed_ipv6() {
+        let server = SmtpServer::from_str("[::1]:25").unwrap();
+        assert_eq!(server.host(), "::1");
+        assert_eq!(server.port(), 25);
+        assert_eq!(server.tls(), TlsMode::Opportunistic);
+        assert_eq!(server.to_string(), "[::1]:25");
+        assert_eq!(SmtpServer::from_str(&server.to_string()).unwrap(), server);
+
+        let server = SmtpServer::from_str("[2001:db8::1]").unwrap();
+        assert_eq!(server.host(), "2001:db8::1");
+        assert_eq!(server.port(), 465);
+    }
+
+    #[test]
+    fn from_str_bare_ipv6() {
+        let server = SmtpServer::from_str("::1").unwrap();
+        assert_eq!(server.host(), "::1");
+        assert_eq!(server.port(), 465);
+        assert_eq!(server.tls(), TlsMode::Implicit);
+    }
+
+    #[test]
+    fn from_str_invalid_bracketed_host() {
+        assert_eq!(
+            SmtpServer::from_str("[::1"),
+            Err(SmtpServerParseError::InvalidHost("[::1".to_owned()))
+        );
+        assert_eq!(
+            SmtpServer::from_str("[::1]25"),
+            Err(SmtpServerParseError::InvalidHost("[::1]25".to_owned()))
+        );
+    }
+
+    #[test]
+    fn from_str_invalid_port() {
+        assert_eq!(
+            SmtpServer::from_str("smtp.example.com:abc"),
+            Err(SmtpServerParseError::InvalidPort("abc".to_owned()))
+        );
+    }
+
+    #[test]
+    fn from_str_empty_host() {
+        assert_eq!(
+            SmtpServer::from_str(":587"),
+            Err(SmtpServerParseError::EmptyHost)
+        );
+    }
+}
@@ -0,0 +1,27 @@
+use lettre::transport::smtp::response::Response;
+
+use crate::{FormattedMessage, MailTransport, SendEmailError, ok_response};
+
+/// A transport which accepts and discards every message.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StubMailTransport;
+
+impl MailTransport for StubMailTransport {
+    fn send_message(&self, _message: &FormattedMessage) -> Result<Response, SendEmailError> {
+        Ok(ok_response())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Email, EmailAccount, send_email_with_transport};
+
+    #[test]
+    fn stub_always_succeeds() {
+        let response =
+            send_email_with_transport(Email::sample(), EmailAccount::sample(), &StubMailTransport)
+                .unwrap();
+        assert!(response.is_positive());
+    }
+}
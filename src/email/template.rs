@@ -1,4 +1,9 @@
-use crate::TemplatePart;
+use indexmap::IndexSet;
+
+use crate::{
+    EmailBody, TemplateError, TemplateField, TemplatePart, TemplatePlaceholder, TemplateVariables,
+    check_unknown, render_nodes, variable_names,
+};
 use bon::Builder;
 use getset::Getters;
 use serde::{Deserialize, Serialize};
@@ -9,8 +14,14 @@ pub struct Template {
     #[getset(get = "pub")]
     subject_format: TemplatePart,
 
+    /// Format of the plain-text body.
     #[getset(get = "pub")]
     body_format: TemplatePart,
+
+    /// Optional format of the HTML body, if set the plain-text body is sent as fallback.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[getset(get = "pub")]
+    html_body_format: Option<TemplatePart>,
 }
 
 impl Template {
@@ -19,4 +30,155 @@ impl Template {
         let body = self.body_format.materialize_with(replacements);
         (subject, body)
     }
+
+    /// Like [`Template::materialize_with`] but also materializes the HTML body, if any.
+    pub fn materialize_email_with(&self, replacements: &[(String, String)]) -> (String, EmailBody) {
+        let (subject, text) = self.materialize_with(replacements);
+        let body = match &self.html_body_format {
+            Some(html) => EmailBody::alternative(text, html.materialize_with(replacements)),
+            None => EmailBody::from_text(text),
+        };
+        (subject, body)
+    }
+
+    /// The parts of this template, the HTML body only if set.
+    pub fn fields(&self) -> impl Iterator<Item = (TemplateField, &TemplatePart)> {
+        [
+            (TemplateField::Subject, Some(&self.subject_format)),
+            (TemplateField::Body, Some(&self.body_format)),
+            (TemplateField::HtmlBody, self.html_body_format.as_ref()),
+        ]
+        .into_iter()
+        .filter_map(|(field, part)| part.map(|part| (field, part)))
+    }
+
+    /// All placeholders in this template with the part they occur in, in order
+    /// of subject, body and HTML body.
+    pub fn placeholders(&self) -> Result<Vec<(TemplateField, TemplatePlaceholder)>, TemplateError> {
+        let mut placeholders = Vec::new();
+        for (field, part) in self.fields() {
+            placeholders.extend(
+                part.placeholders()?
+                    .into_iter()
+                    .map(|placeholder| (field, placeholder)),
+            );
+        }
+        Ok(placeholders)
+    }
+
+    /// Names of the variables used by any part of this template, in order of first use.
+    pub fn variables(&self) -> Result<IndexSet<String>, TemplateError> {
+        let mut variables = IndexSet::new();
+        for (_, part) in self.fields() {
+            variables.extend(part.variables()?);
+        }
+        Ok(variables)
+    }
+
+    /// Renders the subject and body with `variables`, see [`TemplatePart::render`].
+    ///
+    /// Every variable must be used by at least one of the subject, body and
+    /// HTML body, otherwise this fails with [`TemplateError::UnknownVariables`].
+    pub fn render(
+        &self,
+        variables: &TemplateVariables,
+    ) -> Result<(String, EmailBody), TemplateError> {
+        self.render_with_builtins(variables, &TemplateVariables::new())
+    }
+
+    /// Like [`Template::render`] but with built-in variables, see
+    /// [`TemplatePart::render_with_builtins`].
+    pub fn render_with_builtins(
+        &self,
+        variables: &TemplateVariables,
+        builtins: &TemplateVariables,
+    ) -> Result<(String, EmailBody), TemplateError> {
+        let subject = self.subject_format.parse()?;
+        let text = self.body_format.parse()?;
+        let html = self
+            .html_body_format
+            .as_ref()
+            .map(TemplatePart::parse)
+            .transpose()?;
+
+        let mut names = variable_names(&subject);
+        names.extend(variable_names(&text));
+        if let Some(html) = &html {
+            names.extend(variable_names(html));
+        }
+        check_unknown(&names, variables)?;
+
+        let variables = &variables.layered_over(builtins);
+        let subject = render_nodes(&subject, variables)?;
+        let text = render_nodes(&text, variables)?;
+        let body = match html {
+            Some(html) => EmailBody::alternative(text, render_nodes(&html, variables)?),
+            None => EmailBody::from_text(text),
+        };
+        Ok((subject, body))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn materialize_email_with_html() {
+        let template = Template::builder()
+            .subject_format("Invoice <INV_NO>".into())
+            .body_format("Invoice <INV_NO> attached".into())
+            .html_body_format("<b>Invoice <INV_NO></b> attached".into())
+            .build();
+        let (subject, body) =
+            template.materialize_email_with(&[("<INV_NO>".to_owned(), "42".to_owned())]);
+        assert_eq!(subject, "Invoice 42");
+        assert_eq!(
+            body,
+            EmailBody::alternative("Invoice 42 attached", "<b>Invoice 42</b> attached")
+        );
+    }
+
+    #[test]
+    fn render_variables_used_by_any_part() {
+        let template = Template::builder()
+            .subject_format("Invoice <INV_NO>".into())
+            .body_format("Please pay <AMOUNT>".into())
+            .html_body_format("<p>Please pay <b><AMOUNT></b></p>".into())
+            .build();
+        let variables = TemplateVariables::new()
+            .with("INV_NO", 42)
+            .with("AMOUNT", "100 EUR");
+        assert_eq!(
+            template.render(&variables).unwrap(),
+            (
+                "Invoice 42".to_owned(),
+                EmailBody::alternative("Please pay 100 EUR", "<p>Please pay <b>100 EUR</b></p>")
+            )
+        );
+    }
+
+    #[test]
+    fn variables_of_all_parts() {
+        let template = Template::builder()
+            .subject_format("Invoice <INV_NO>".into())
+            .body_format("Hi <NAME>, invoice <INV_NO>".into())
+            .html_body_format("<p>Due <DUE_DATE></p>".into())
+            .build();
+        assert_eq!(
+            template.variables().unwrap(),
+            IndexSet::from(["INV_NO", "NAME", "DUE_DATE"].map(str::to_owned))
+        );
+        let placeholders = template.placeholders().unwrap();
+        assert_eq!(placeholders.len(), 4);
+        assert_eq!(placeholders[3].0, TemplateField::HtmlBody);
+        assert_eq!(placeholders[3].1.name(), "DUE_DATE");
+    }
+
+    #[test]
+    fn deserialize_without_html_body_format() {
+        let template: Template =
+            serde_json::from_str(r#"{"subject_format":"Hi","body_format":"Body"}"#).unwrap();
+        assert_eq!(template.html_body_format(), &None);
+    }
 }
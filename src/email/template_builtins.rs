@@ -0,0 +1,179 @@
+use bon::Builder;
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use getset::{Getters, WithSetters};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use crate::{EmailAccount, Recipient, TemplateVariables};
+
+/// Configuration of the built-in template variables, which are available in
+/// every template without being supplied and behave the same in all build profiles:
+///
+/// - `<RNG>`: a random unsigned 64 bit integer.
+/// - `<RANDOM_ID>`: 16 random hexadecimal digits.
+/// - `<NOW>`: the current date and time in [`TemplateBuiltins::timezone`],
+///   typically formatted with a filter, e.g. `<NOW | date:"%H:%M">`.
+/// - `<TODAY>`: the current date in [`TemplateBuiltins::timezone`].
+/// - `<SENDER_NAME>`: the name of the sender, if set.
+/// - `<RECIPIENT_NAME>`: the name of the recipient, if set and it has a name,
+///   e.g. `<RECIPIENT_NAME | default:"there">`. An email to several recipients
+///   is rendered once, with the first of them as the recipient.
+/// - `<MESSAGE_ID>`: the `Message-ID` of the email, without angle brackets, if
+///   the sender is set.
+///
+/// Set a seed and the current time to get the same values every time, e.g. in tests.
+#[derive(Debug, Clone, PartialEq, Eq, Builder, Getters, WithSetters)]
+pub struct TemplateBuiltins {
+    /// Seed for the random values, if `None` they are seeded by the OS.
+    #[getset(get = "pub", set_with = "pub")]
+    seed: Option<u64>,
+
+    /// The current time, if `None` the system clock is used.
+    #[getset(get = "pub", set_with = "pub")]
+    now: Option<DateTime<Utc>>,
+
+    /// Timezone of `<NOW>` and `<TODAY>`.
+    #[builder(default = Tz::UTC)]
+    #[getset(get = "pub", set_with = "pub")]
+    timezone: Tz,
+
+    #[getset(get = "pub", set_with = "pub")]
+    sender: Option<EmailAccount>,
+
+    #[getset(get = "pub", set_with = "pub")]
+    recipient: Option<Recipient>,
+}
+
+impl Default for TemplateBuiltins {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+impl TemplateBuiltins {
+    pub const RNG: &str = "RNG";
+    pub const RANDOM_ID: &str = "RANDOM_ID";
+    pub const NOW: &str = "NOW";
+    pub const TODAY: &str = "TODAY";
+    pub const SENDER_NAME: &str = "SENDER_NAME";
+    pub const RECIPIENT_NAME: &str = "RECIPIENT_NAME";
+    pub const MESSAGE_ID: &str = "MESSAGE_ID";
+
+    /// Names of all built-in variables.
+    pub const NAMES: [&str; 7] = [
+        Self::RNG,
+        Self::RANDOM_ID,
+        Self::NOW,
+        Self::TODAY,
+        Self::SENDER_NAME,
+        Self::RECIPIENT_NAME,
+        Self::MESSAGE_ID,
+    ];
+
+    /// Generates the values of the built-in variables, the random values are
+    /// new for every call unless a seed is set.
+    pub fn variables(&self) -> TemplateVariables {
+        let mut rng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_os_rng(),
+        };
+        let now = self
+            .now
+            .unwrap_or_else(Utc::now)
+            .with_timezone(&self.timezone)
+            .fixed_offset();
+
+        let mut variables = TemplateVariables::new()
+            .with(Self::RNG, rng.random::<u64>().to_string())
+            .with(Self::RANDOM_ID, format!("{:016x}", rng.random::<u64>()))
+            .with(Self::NOW, now)
+            .with(Self::TODAY, now.date_naive());
+        if let Some(sender) = &self.sender {
+            variables.insert(Self::SENDER_NAME, sender.name().as_str());
+            variables.insert(
+                Self::MESSAGE_ID,
+                format!(
+                    "{:016x}.{}@{}",
+                    rng.random::<u64>(),
+                    now.timestamp(),
+                    sender.email().domain()
+                ),
+            );
+        }
+        if let Some(name) = self
+            .recipient
+            .as_ref()
+            .and_then(|recipient| recipient.name().as_ref())
+        {
+            variables.insert(Self::RECIPIENT_NAME, name.as_str());
+        }
+        variables
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TemplateValue;
+    use chrono::TimeZone as _;
+
+    fn seeded() -> TemplateBuiltins {
+        TemplateBuiltins::builder()
+            .seed(42)
+            .now(Utc.with_ymd_and_hms(2025, 1, 31, 23, 30, 0).unwrap())
+            .timezone(Tz::Europe__Stockholm)
+            .sender(EmailAccount::sample_alice())
+            .recipient(Recipient::sample_bob())
+            .build()
+    }
+
+    #[test]
+    fn seeded_variables_are_deterministic() {
+        assert_eq!(seeded().variables(), seeded().variables());
+        assert_ne!(
+            seeded().variables(),
+            seeded().with_seed(Some(7)).variables()
+        );
+    }
+
+    #[test]
+    fn now_and_today_in_timezone() {
+        let variables = seeded().variables();
+        assert_eq!(
+            variables.get(TemplateBuiltins::TODAY).unwrap().to_string(),
+            "2025-02-01"
+        );
+        assert_eq!(
+            variables.get(TemplateBuiltins::NOW).unwrap().to_string(),
+            "2025-02-01T00:30:00+01:00"
+        );
+    }
+
+    #[test]
+    fn sender_and_recipient() {
+        let variables = seeded().variables();
+        assert_eq!(
+            variables.get(TemplateBuiltins::SENDER_NAME),
+            Some(&TemplateValue::from("Alice Smith"))
+        );
+        assert_eq!(
+            variables.get(TemplateBuiltins::RECIPIENT_NAME),
+            Some(&TemplateValue::from("Bob Johnson"))
+        );
+        assert!(
+            variables
+                .get(TemplateBuiltins::MESSAGE_ID)
+                .unwrap()
+                .to_string()
+                .ends_with("@example.com")
+        );
+    }
+
+    #[test]
+    fn without_sender_and_recipient() {
+        let variables = TemplateBuiltins::default().variables();
+        assert!(!variables.contains(TemplateBuiltins::SENDER_NAME));
+        assert!(!variables.contains(TemplateBuiltins::RECIPIENT_NAME));
+        assert!(!variables.contains(TemplateBuiltins::MESSAGE_ID));
+    }
+}
@@ -0,0 +1,58 @@
+use std::fmt::{Display, Formatter};
+
+/// Errors that can occur when parsing or rendering a template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateError {
+    /// The template is malformed at `line` and `column`, both starting at 1.
+    Syntax {
+        line: usize,
+        column: usize,
+        reason: String,
+    },
+
+    /// The template uses placeholders for which no value was supplied.
+    MissingVariables { names: Vec<String> },
+
+    /// Values were supplied for variables the template does not use, which
+    /// typically means a placeholder or variable name is misspelled.
+    UnknownVariables { names: Vec<String> },
+
+    /// `<#each NAME as ITEM>` was used with a value which is not a list.
+    NotAList { name: String },
+
+    /// A filter cannot be applied to the value, e.g. `number` to a non-numeric text.
+    InvalidFilterInput { filter: String, value: String },
+}
+
+impl Display for TemplateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Syntax {
+                line,
+                column,
+                reason,
+            } => write!(
+                f,
+                "invalid template at line {line}, column {column}: {reason}"
+            ),
+            Self::MissingVariables { names } => {
+                write!(
+                    f,
+                    "missing values for template variables: {}",
+                    names.join(", ")
+                )
+            }
+            Self::UnknownVariables { names } => {
+                write!(f, "unknown template variables: {}", names.join(", "))
+            }
+            Self::NotAList { name } => {
+                write!(f, "template variable {name} is not a list")
+            }
+            Self::InvalidFilterInput { filter, value } => {
+                write!(f, "cannot apply template filter `{filter}` to '{value}'")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
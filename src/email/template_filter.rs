@@ -0,0 +1,229 @@
+use std::fmt::{Display, Formatter, Write as _};
+
+use chrono::{
+    DateTime, NaiveDate, NaiveDateTime,
+    format::{Item, StrftimeItems},
+};
+
+use crate::{TemplateError, TemplateValue};
+
+/// A formatting filter applied to a placeholder, e.g. `upper` in `<NAME | upper>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum TemplateFilter {
+    /// `upper`, the value in uppercase.
+    Upper,
+    /// `lower`, the value in lowercase.
+    Lower,
+    /// `default:"text"`, the text if the variable is missing or empty.
+    Default(String),
+    /// `number:2`, the number with the given count of decimals, at most
+    /// [`TemplateFilter::MAX_DECIMALS`].
+    Number { decimals: usize },
+    /// `currency:"EUR"`, the number with two decimals followed by the currency.
+    Currency(String),
+    /// `date:"%Y-%m-%d"`, the date or date time formatted with a `strftime` format.
+    Date(String),
+}
+
+impl Display for TemplateFilter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Upper => write!(f, "upper"),
+            Self::Lower => write!(f, "lower"),
+            Self::Default(text) => write!(f, "default:\"{text}\""),
+            Self::Number { decimals } => write!(f, "number:{decimals}"),
+            Self::Currency(currency) => write!(f, "currency:\"{currency}\""),
+            Self::Date(format) => write!(f, "date:\"{format}\""),
+        }
+    }
+}
+
+/// The unquoted text of `argument`, which must be in double quotes.
+fn quoted(filter: &str, argument: Option<&str>) -> Result<String, String> {
+    argument
+        .and_then(|argument| argument.strip_prefix('"'))
+        .and_then(|argument| argument.strip_suffix('"'))
+        .filter(|text| !text.contains('"'))
+        .map(str::to_owned)
+        .ok_or_else(|| format!("filter `{filter}` expects a text argument in double quotes"))
+}
+
+impl TemplateFilter {
+    /// The largest count of decimals of the `number` filter, an `f64` has at
+    /// most 17 significant digits.
+    pub const MAX_DECIMALS: usize = 17;
+
+    /// Parses a filter, e.g. `number:2`, failing with the reason.
+    pub(crate) fn parse(filter: &str) -> Result<Self, String> {
+        let (name, argument) = match filter.split_once(':') {
+            Some((name, argument)) => (name.trim(), Some(argument.trim())),
+            None => (filter.trim(), None),
+        };
+        let no_argument = |filter: Self| match argument {
+            None => Ok(filter),
+            Some(_) => Err(format!("filter `{name}` takes no argument")),
+        };
+        match name {
+            "upper" => no_argument(Self::Upper),
+            "lower" => no_argument(Self::Lower),
+            "default" => quoted(name, argument).map(Self::Default),
+            "number" => argument
+                .map_or(Ok(0), str::parse)
+                .ok()
+                .filter(|decimals| *decimals <= Self::MAX_DECIMALS)
+                .map(|decimals| Self::Number { decimals })
+                .ok_or_else(|| {
+                    format!(
+                        "filter `number` expects a count of decimals of at most {}",
+                        Self::MAX_DECIMALS
+                    )
+                }),
+            "currency" => quoted(name, argument).map(Self::Currency),
+            "date" => {
+                let format = quoted(name, argument)?;
+                if StrftimeItems::new(&format).any(|item| item == Item::Error) {
+                    return Err(format!("invalid date format \"{format}\""));
+                }
+                Ok(Self::Date(format))
+            }
+            _ => Err(format!("unknown filter `{name}`")),
+        }
+    }
+
+    /// Whether this filter provides a value for missing variables.
+    pub(crate) fn is_default(&self) -> bool {
+        matches!(self, Self::Default(_))
+    }
+
+    /// Applies this filter to `value`, `None` if the variable is missing.
+    pub(crate) fn apply(
+        &self,
+        value: Option<TemplateValue>,
+    ) -> Result<Option<TemplateValue>, TemplateError> {
+        if let Self::Default(text) = self {
+            return Ok(match value {
+                None => Some(TemplateValue::Text(text.clone())),
+                Some(TemplateValue::Text(value)) if value.is_empty() => {
+                    Some(TemplateValue::Text(text.clone()))
+                }
+                value => value,
+            });
+        }
+        let Some(value) = value else {
+            return Ok(None);
+        };
+        let invalid_input = || TemplateError::InvalidFilterInput {
+            filter: self.to_string(),
+            value: value.to_string(),
+        };
+        let filtered = match self {
+            Self::Default(_) => unreachable!("handled above"),
+            Self::Upper => value.to_string().to_uppercase(),
+            Self::Lower => value.to_string().to_lowercase(),
+            Self::Number { decimals } => {
+                let number = value.as_f64().ok_or_else(invalid_input)?;
+                format!("{number:.decimals$}")
+            }
+            Self::Currency(currency) => {
+                let number = value.as_f64().ok_or_else(invalid_input)?;
+                format!("{number:.2} {currency}")
+            }
+            Self::Date(format) => {
+                let items = StrftimeItems::new(format);
+                let mut formatted = String::new();
+                let written = match &value {
+                    TemplateValue::Date(date) => write!(
+                        formatted,
+                        "{}",
+                        NaiveDateTime::from(*date).format_with_items(items)
+                    ),
+                    TemplateValue::DateTime(date_time) => {
+                        write!(formatted, "{}", date_time.format_with_items(items))
+                    }
+                    TemplateValue::Text(text) => {
+                        if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+                            write!(
+                                formatted,
+                                "{}",
+                                NaiveDateTime::from(date).format_with_items(items)
+                            )
+                        } else if let Ok(date_time) = DateTime::parse_from_rfc3339(text) {
+                            write!(formatted, "{}", date_time.format_with_items(items))
+                        } else {
+                            return Err(invalid_input());
+                        }
+                    }
+                    _ => return Err(invalid_input()),
+                };
+                written.map_err(|_| invalid_input())?;
+                formatted
+            }
+        };
+        Ok(Some(TemplateValue::Text(filtered)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(filter: &str, value: impl Into<TemplateValue>) -> Result<String, TemplateError> {
+        TemplateFilter::parse(filter)
+            .unwrap()
+            .apply(Some(value.into()))
+            .map(|value| value.unwrap().to_string())
+    }
+
+    #[test]
+    fn number_and_currency() {
+        assert_eq!(apply("number:2", 1234.5).unwrap(), "1234.50");
+        assert_eq!(apply("number", "41.6").unwrap(), "42");
+        assert_eq!(apply(r#"currency:"EUR""#, 100).unwrap(), "100.00 EUR");
+        assert_eq!(
+            apply("number:2", "many"),
+            Err(TemplateError::InvalidFilterInput {
+                filter: "number:2".to_owned(),
+                value: "many".to_owned()
+            })
+        );
+    }
+
+    #[test]
+    fn date() {
+        let date = NaiveDate::from_ymd_opt(2025, 1, 31).unwrap();
+        assert_eq!(apply(r#"date:"%d/%m/%Y""#, date).unwrap(), "31/01/2025");
+        assert_eq!(
+            apply(r#"date:"%B %-d""#, "2025-01-31").unwrap(),
+            "January 31"
+        );
+        assert!(apply(r#"date:"%z""#, date).is_err());
+    }
+
+    #[test]
+    fn default() {
+        let filter = TemplateFilter::parse(r#"default:"there""#).unwrap();
+        assert_eq!(
+            filter.apply(None).unwrap(),
+            Some(TemplateValue::from("there"))
+        );
+        assert_eq!(
+            filter.apply(Some("".into())).unwrap(),
+            Some(TemplateValue::from("there"))
+        );
+        assert_eq!(
+            filter.apply(Some("Jane".into())).unwrap(),
+            Some(TemplateValue::from("Jane"))
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(TemplateFilter::parse("shout").is_err());
+        assert!(TemplateFilter::parse("upper:1").is_err());
+        assert!(TemplateFilter::parse("default:there").is_err());
+        assert!(TemplateFilter::parse(r#"date:"%Q""#).is_err());
+        assert!(TemplateFilter::parse("number:17").is_ok());
+        assert!(TemplateFilter::parse("number:18").is_err());
+        assert!(TemplateFilter::parse("number:99999999999").is_err());
+    }
+}
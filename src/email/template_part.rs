@@ -1,7 +1,36 @@
 use derive_more::From;
+use indexmap::IndexSet;
 use serde_with::{DeserializeFromStr, SerializeDisplay};
 
+use crate::{
+    TemplateError, TemplateNode, TemplatePlaceholder, TemplateVariables, check_unknown,
+    parse_template, render_nodes, variable_names, variable_references,
+};
+
 /// Free-form email template text that may contain placeholder tokens.
+///
+/// A placeholder is an uppercase name in angle brackets, e.g. `<INV_NO>`, where
+/// the name starts with an ASCII uppercase letter followed by ASCII uppercase
+/// letters, digits or underscores. Fields of map values are accessed with dots,
+/// e.g. `<INVOICE.NUMBER>`, and filters are applied with pipes, e.g.
+/// `<NAME | upper>`, see below.
+///
+/// Blocks are written as:
+/// - `<#if OVERDUE>…<#else>…</#if>`, where `<#else>` is optional and
+///   `<#if !OVERDUE>` negates, see [`crate::TemplateValue::is_truthy`].
+/// - `<#each ITEMS as ITEM>…</#each>`, repeated for every item of the list
+///   `ITEMS`, which is available as `<ITEM>` inside the block.
+///
+/// The filters are `upper`, `lower`, `default:"text"` (used if the variable is
+/// missing or empty), `number:2` (count of decimals), `currency:"EUR"` and
+/// `date:"%Y-%m-%d"` (a `strftime` format).
+///
+/// The built-in variables, e.g. `<NOW>` and `<RANDOM_ID>`, are listed in
+/// [`crate::TemplateBuiltins`], see [`TemplatePart::render_with_builtins`].
+///
+/// Anything else is literal text, so HTML tags like `<b>` need no escaping,
+/// and `\<` is a literal `<`, which allows writing a placeholder verbatim,
+/// e.g. `\<INV_NO>`.
 #[derive(
     Debug,
     Clone,
@@ -18,23 +47,85 @@ pub struct TemplatePart(String);
 
 impl TemplatePart {
     /// Replace each known placeholder with its provided value.
+    ///
+    /// Replacements are done in a single pass, preferring the longest matching
+    /// placeholder, so replaced values are never themselves replaced.
+    /// Placeholders without a provided value are left as is, prefer
+    /// [`TemplatePart::render`] which reports them.
     pub fn materialize_with(&self, replacements: &[(String, String)]) -> String {
-        let mut raw = self.0.clone();
+        let mut replacements = replacements
+            .iter()
+            .filter(|(placeholder, _)| !placeholder.is_empty())
+            .map(|(placeholder, value)| (placeholder.as_str(), value.clone()))
+            .collect::<Vec<_>>();
 
-        for (placeholder, value) in replacements {
-            raw = raw.replace(placeholder, value);
-        }
+        replacements.sort_by_key(|(placeholder, _)| std::cmp::Reverse(placeholder.len()));
 
-        #[cfg(debug_assertions)]
-        {
-            let rng = "<RNG>";
-            if raw.contains(rng) {
-                let rnd: u64 = rand::random();
-                raw = raw.replace(rng, rnd.to_string().as_str());
+        let raw = self.0.as_str();
+        let mut materialized = String::with_capacity(raw.len());
+        let mut index = 0;
+        while let Some(rest) = raw.get(index..).filter(|rest| !rest.is_empty()) {
+            match replacements
+                .iter()
+                .find(|(placeholder, _)| rest.starts_with(placeholder))
+            {
+                Some((placeholder, value)) => {
+                    materialized.push_str(value);
+                    index += placeholder.len();
+                }
+                None => {
+                    let next = rest.chars().next().expect("rest is not empty");
+                    materialized.push(next);
+                    index += next.len_utf8();
+                }
             }
         }
+        materialized
+    }
+
+    /// Substitutes the placeholders, e.g. `<INV_NO>`, with the values of
+    /// `variables` in a single pass.
+    ///
+    /// Fails with [`TemplateError::MissingVariables`] if a placeholder has no
+    /// value and with [`TemplateError::UnknownVariables`] if a variable is not
+    /// used by this template part.
+    pub fn render(&self, variables: &TemplateVariables) -> Result<String, TemplateError> {
+        self.render_with_builtins(variables, &TemplateVariables::new())
+    }
+
+    /// Like [`TemplatePart::render`] but placeholders may also use `builtins`,
+    /// typically [`crate::TemplateBuiltins::variables`], which are overridden
+    /// by `variables` and never reported as unknown.
+    pub fn render_with_builtins(
+        &self,
+        variables: &TemplateVariables,
+        builtins: &TemplateVariables,
+    ) -> Result<String, TemplateError> {
+        let nodes = parse_template(&self.0)?;
+        check_unknown(&variable_names(&nodes), variables)?;
+        render_nodes(&nodes, &variables.layered_over(builtins))
+    }
 
-        raw
+    /// All placeholders in this template part, in order of occurrence.
+    pub fn placeholders(&self) -> Result<Vec<TemplatePlaceholder>, TemplateError> {
+        Ok(variable_references(&self.parse()?)
+            .into_iter()
+            .map(|reference| {
+                TemplatePlaceholder::new(reference.name.to_owned(), reference.span.clone(), &self.0)
+            })
+            .collect())
+    }
+
+    /// Names of the variables used by this template part, in order of first use.
+    pub fn variables(&self) -> Result<IndexSet<String>, TemplateError> {
+        Ok(variable_names(&self.parse()?)
+            .into_iter()
+            .map(str::to_owned)
+            .collect())
+    }
+
+    pub(crate) fn parse(&self) -> Result<Vec<TemplateNode>, TemplateError> {
+        parse_template(&self.0)
     }
 }
 
@@ -43,3 +134,97 @@ impl Default for TemplatePart {
         Self("Invoice <INV_NO> from <FROM_CO>".to_owned())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn materialize_with_is_single_pass() {
+        let part = TemplatePart::from("<A> and <B>");
+        let replacements = [
+            ("<A>".to_owned(), "<B>".to_owned()),
+            ("<B>".to_owned(), "b".to_owned()),
+        ];
+        assert_eq!(part.materialize_with(&replacements), "<B> and b");
+    }
+
+    #[test]
+    fn placeholders_with_positions() {
+        let part = TemplatePart::from("Hej <NAME>,\nfaktura <INV_NO> för <NAME>");
+        let placeholders = part.placeholders().unwrap();
+        assert_eq!(
+            placeholders
+                .iter()
+                .map(|p| (p.name().as_str(), p.line(), p.column()))
+                .collect::<Vec<_>>(),
+            [("NAME", 1, 5), ("INV_NO", 2, 9), ("NAME", 2, 22)]
+        );
+        assert_eq!(placeholders[1].span(), &(20..28));
+        assert_eq!(
+            part.variables().unwrap(),
+            IndexSet::from(["NAME".to_owned(), "INV_NO".to_owned()])
+        );
+    }
+
+    #[test]
+    fn render() {
+        let part = TemplatePart::default();
+        let variables = TemplateVariables::new()
+            .with("INV_NO", 42)
+            .with("FROM_CO", "Acme");
+        assert_eq!(part.render(&variables).unwrap(), "Invoice 42 from Acme");
+    }
+
+    #[test]
+    fn render_missing_variable() {
+        let part = TemplatePart::default();
+        let variables = TemplateVariables::from([("INV_NO", 42)]);
+        assert_eq!(
+            part.render(&variables),
+            Err(TemplateError::MissingVariables {
+                names: vec!["FROM_CO".to_owned()]
+            })
+        );
+    }
+
+    #[test]
+    fn render_with_builtins() {
+        let part = TemplatePart::from("Invoice <INV_NO> of <TODAY | date:\"%d/%m\">");
+        let builtins = crate::TemplateBuiltins::builder()
+            .now("2025-03-14T12:00:00Z".parse().unwrap())
+            .build()
+            .variables();
+        let variables = TemplateVariables::from([("INV_NO", 42)]);
+        assert_eq!(
+            part.render_with_builtins(&variables, &builtins).unwrap(),
+            "Invoice 42 of 14/03"
+        );
+        assert_eq!(
+            part.render_with_builtins(
+                &variables.with(
+                    "TODAY",
+                    chrono::NaiveDate::from_ymd_opt(2020, 1, 2).unwrap()
+                ),
+                &builtins
+            )
+            .unwrap(),
+            "Invoice 42 of 02/01"
+        );
+    }
+
+    #[test]
+    fn render_unknown_variable() {
+        let part = TemplatePart::default();
+        let variables = TemplateVariables::new()
+            .with("INV_NO", 42)
+            .with("FROM_CO", "Acme")
+            .with("INV_N0", 42);
+        assert_eq!(
+            part.render(&variables),
+            Err(TemplateError::UnknownVariables {
+                names: vec!["INV_N0".to_owned()]
+            })
+        );
+    }
+}
@@ -0,0 +1,57 @@
+use std::ops::Range;
+
+use getset::{CopyGetters, Getters};
+
+use crate::{line_and_column, path_root};
+
+/// A part of a [`crate::Template`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, derive_more::Display)]
+pub enum TemplateField {
+    #[display("subject")]
+    Subject,
+    #[display("body")]
+    Body,
+    #[display("HTML body")]
+    HtmlBody,
+}
+
+/// An occurrence of a template variable in a [`crate::TemplatePart`], e.g. the
+/// placeholder `<INV_NO>` or the tag `<#if OVERDUE>`.
+///
+/// Fields of the items of `<#each>` loops are not template variables.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Getters, CopyGetters)]
+pub struct TemplatePlaceholder {
+    /// Name of the variable as written, without angle brackets, e.g. `"INV_NO"`
+    /// or `"INVOICE.NUMBER"` for a field of a map.
+    #[getset(get = "pub")]
+    name: String,
+
+    /// Byte range of the placeholder or tag, including angle brackets, in the template text.
+    #[getset(get = "pub")]
+    span: Range<usize>,
+
+    /// Line of the placeholder in the template text, starting at 1.
+    #[getset(get_copy = "pub")]
+    line: usize,
+
+    /// Column of the placeholder in its line, in characters, starting at 1.
+    #[getset(get_copy = "pub")]
+    column: usize,
+}
+
+impl TemplatePlaceholder {
+    pub(crate) fn new(name: String, span: Range<usize>, source: &str) -> Self {
+        let (line, column) = line_and_column(source, span.start);
+        Self {
+            name,
+            span,
+            line,
+            column,
+        }
+    }
+
+    /// The variable supplying the value, e.g. `"INVOICE"` for `"INVOICE.NUMBER"`.
+    pub fn variable(&self) -> &str {
+        path_root(&self.name)
+    }
+}
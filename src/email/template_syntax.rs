@@ -0,0 +1,699 @@
+use std::ops::Range;
+
+use crate::{TemplateError, TemplateFilter, TemplateValue, TemplateVariables};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum TemplateNode {
+    Text(String),
+    /// `<NAME>` or `<NAME | filter | …>`, where the name may access fields, e.g. `ITEM.AMOUNT`.
+    Variable {
+        name: String,
+        filters: Vec<TemplateFilter>,
+        /// Byte range of the placeholder, including angle brackets, in the source.
+        span: Range<usize>,
+    },
+    /// `<#if NAME>…<#else>…</#if>`, or `<#if !NAME>` if `negated`.
+    If {
+        name: String,
+        negated: bool,
+        span: Range<usize>,
+        then: Vec<TemplateNode>,
+        otherwise: Vec<TemplateNode>,
+    },
+    /// `<#each NAME as ITEM>…</#each>`.
+    Each {
+        name: String,
+        item: String,
+        span: Range<usize>,
+        body: Vec<TemplateNode>,
+    },
+}
+
+/// A tag or text of the source, before blocks are nested.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Text(String),
+    Variable {
+        name: String,
+        filters: Vec<TemplateFilter>,
+        span: Range<usize>,
+    },
+    If {
+        name: String,
+        negated: bool,
+        span: Range<usize>,
+    },
+    Else {
+        span: Range<usize>,
+    },
+    EndIf {
+        span: Range<usize>,
+    },
+    Each {
+        name: String,
+        item: String,
+        span: Range<usize>,
+    },
+    EndEach {
+        span: Range<usize>,
+    },
+}
+
+/// Line and column, in characters, of the byte `offset` in `source`, both starting at 1.
+pub(crate) fn line_and_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset];
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
+fn syntax_error(source: &str, offset: usize, reason: impl Into<String>) -> TemplateError {
+    let (line, column) = line_and_column(source, offset);
+    TemplateError::Syntax {
+        line,
+        column,
+        reason: reason.into(),
+    }
+}
+
+fn is_name(name: &str) -> bool {
+    let bytes = name.as_bytes();
+    bytes.first().is_some_and(u8::is_ascii_uppercase)
+        && bytes
+            .iter()
+            .all(|byte| byte.is_ascii_uppercase() || byte.is_ascii_digit() || *byte == b'_')
+}
+
+/// Whether `path` is a name, optionally followed by field names, e.g. `ITEM.AMOUNT`.
+fn is_path(path: &str) -> bool {
+    path.split('.').all(is_name)
+}
+
+/// The variable a `path` refers to, e.g. `ITEM` for `ITEM.AMOUNT`.
+pub(crate) fn path_root(path: &str) -> &str {
+    path.split_once('.').map_or(path, |(root, _)| root)
+}
+
+/// Index of the first `>` at or after `from` which is not within double quotes.
+fn closing_bracket(source: &str, from: usize) -> Option<usize> {
+    let mut quoted = false;
+    source[from..]
+        .bytes()
+        .position(|byte| {
+            if byte == b'"' {
+                quoted = !quoted;
+            }
+            byte == b'>' && !quoted
+        })
+        .map(|offset| from + offset)
+}
+
+/// Splits `filters` on `|` outside of double quotes.
+fn split_filters(filters: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quoted = false;
+    let mut start = 0;
+    for (index, byte) in filters.bytes().enumerate() {
+        match byte {
+            b'"' => quoted = !quoted,
+            b'|' if !quoted => {
+                parts.push(&filters[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&filters[start..]);
+    parts
+}
+
+/// The `<#…>` or `</#…>` tag starting at `start`.
+fn block_tag(source: &str, start: usize, closing: bool) -> Result<Token, TemplateError> {
+    let content_start = start + if closing { 3 } else { 2 };
+    let end = closing_bracket(source, content_start)
+        .ok_or_else(|| syntax_error(source, start, "unterminated tag"))?;
+    let span = start..end + 1;
+    let words = source[content_start..end]
+        .split_whitespace()
+        .collect::<Vec<_>>();
+    let invalid = || {
+        syntax_error(
+            source,
+            start,
+            format!("invalid tag `{}`", &source[span.clone()]),
+        )
+    };
+    let token = match (closing, words.as_slice()) {
+        (false, ["if", name]) => {
+            let (name, negated) = match name.strip_prefix('!') {
+                Some(name) => (name, true),
+                None => (*name, false),
+            };
+            if !is_path(name) {
+                return Err(invalid());
+            }
+            Token::If {
+                name: name.to_string(),
+                negated,
+                span,
+            }
+        }
+        (false, ["else"]) => Token::Else { span },
+        (false, ["each", name, "as", item]) if is_path(name) && is_name(item) => Token::Each {
+            name: name.to_string(),
+            item: item.to_string(),
+            span,
+        },
+        (true, ["if"]) => Token::EndIf { span },
+        (true, ["each"]) => Token::EndEach { span },
+        _ => return Err(invalid()),
+    };
+    Ok(token)
+}
+
+/// The placeholder starting at `start`, `None` if it is literal text, e.g. an HTML tag.
+fn placeholder(source: &str, start: usize) -> Result<Option<Token>, TemplateError> {
+    let bytes = source.as_bytes();
+    let name_start = start + 1;
+    let name_end = bytes[name_start..]
+        .iter()
+        .position(|byte| {
+            !(byte.is_ascii_uppercase() || byte.is_ascii_digit() || *byte == b'_' || *byte == b'.')
+        })
+        .map_or(bytes.len(), |offset| name_start + offset);
+    let name = &source[name_start..name_end];
+    if !is_path(name) {
+        return Ok(None);
+    }
+    let rest = source[name_end..].trim_start();
+    let after_name = bytes.len() - rest.len();
+    if rest.starts_with('>') {
+        return Ok(Some(Token::Variable {
+            name: name.to_owned(),
+            filters: Vec::new(),
+            span: start..after_name + 1,
+        }));
+    }
+    if !rest.starts_with('|') {
+        return Ok(None);
+    }
+    let end = closing_bracket(source, after_name)
+        .ok_or_else(|| syntax_error(source, start, "unterminated placeholder"))?;
+    let filters = split_filters(&source[after_name + 1..end])
+        .into_iter()
+        .map(|filter| {
+            TemplateFilter::parse(filter).map_err(|reason| syntax_error(source, start, reason))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Some(Token::Variable {
+        name: name.to_owned(),
+        filters,
+        span: start..end + 1,
+    }))
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, TemplateError> {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    let mut text = String::new();
+    let mut literal_start = 0;
+    let mut index = 0;
+    while index < bytes.len() {
+        let token = match bytes[index] {
+            b'\\' if bytes.get(index + 1) == Some(&b'<') => {
+                text.push_str(&source[literal_start..index]);
+                text.push('<');
+                index += 2;
+                literal_start = index;
+                continue;
+            }
+            b'<' if source[index..].starts_with("<#") => Some(block_tag(source, index, false)?),
+            b'<' if source[index..].starts_with("</#") => Some(block_tag(source, index, true)?),
+            b'<' => placeholder(source, index)?,
+            _ => None,
+        };
+        let Some(token) = token else {
+            index += 1;
+            continue;
+        };
+        text.push_str(&source[literal_start..index]);
+        if !text.is_empty() {
+            tokens.push(Token::Text(std::mem::take(&mut text)));
+        }
+        index = match &token {
+            Token::Variable { span, .. }
+            | Token::If { span, .. }
+            | Token::Else { span }
+            | Token::EndIf { span }
+            | Token::Each { span, .. }
+            | Token::EndEach { span } => span.end,
+            Token::Text(_) => unreachable!("text is never a tag"),
+        };
+        literal_start = index;
+        tokens.push(token);
+    }
+    text.push_str(&source[literal_start..]);
+    if !text.is_empty() {
+        tokens.push(Token::Text(text));
+    }
+    Ok(tokens)
+}
+
+/// Nests `tokens` up to the first `<#else>`, `</#if>` or `</#each>`, which is
+/// returned together with the nodes.
+fn nest(
+    source: &str,
+    tokens: &mut impl Iterator<Item = Token>,
+) -> Result<(Vec<TemplateNode>, Option<Token>), TemplateError> {
+    let mut nodes = Vec::new();
+    while let Some(token) = tokens.next() {
+        match token {
+            Token::Text(text) => nodes.push(TemplateNode::Text(text)),
+            Token::Variable {
+                name,
+                filters,
+                span,
+            } => nodes.push(TemplateNode::Variable {
+                name,
+                filters,
+                span,
+            }),
+            Token::If {
+                name,
+                negated,
+                span,
+            } => {
+                let unclosed = || syntax_error(source, span.start, "unclosed `<#if>`");
+                let (then, end) = nest(source, tokens)?;
+                let otherwise = match end {
+                    Some(Token::EndIf { .. }) => Vec::new(),
+                    Some(Token::Else { .. }) => match nest(source, tokens)? {
+                        (otherwise, Some(Token::EndIf { .. })) => otherwise,
+                        (_, Some(end)) => return Err(unexpected(source, &end)),
+                        (_, None) => return Err(unclosed()),
+                    },
+                    Some(end) => return Err(unexpected(source, &end)),
+                    None => return Err(unclosed()),
+                };
+                nodes.push(TemplateNode::If {
+                    name,
+                    negated,
+                    span,
+                    then,
+                    otherwise,
+                });
+            }
+            Token::Each { name, item, span } => {
+                let body = match nest(source, tokens)? {
+                    (body, Some(Token::EndEach { .. })) => body,
+                    (_, Some(end)) => return Err(unexpected(source, &end)),
+                    (_, None) => {
+                        return Err(syntax_error(source, span.start, "unclosed `<#each>`"));
+                    }
+                };
+                nodes.push(TemplateNode::Each {
+                    name,
+                    item,
+                    span,
+                    body,
+                });
+            }
+            end @ (Token::Else { .. } | Token::EndIf { .. } | Token::EndEach { .. }) => {
+                return Ok((nodes, Some(end)));
+            }
+        }
+    }
+    Ok((nodes, None))
+}
+
+fn unexpected(source: &str, token: &Token) -> TemplateError {
+    let span = match token {
+        Token::Else { span } | Token::EndIf { span } | Token::EndEach { span } => span,
+        _ => unreachable!("only block ends are unexpected"),
+    };
+    syntax_error(
+        source,
+        span.start,
+        format!("unexpected `{}`", &source[span.clone()]),
+    )
+}
+
+pub(crate) fn parse_template(source: &str) -> Result<Vec<TemplateNode>, TemplateError> {
+    let mut tokens = tokenize(source)?.into_iter();
+    match nest(source, &mut tokens)? {
+        (nodes, None) => Ok(nodes),
+        (_, Some(end)) => Err(unexpected(source, &end)),
+    }
+}
+
+/// A reference to a template variable, i.e. not to an `<#each>` item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct VariableReference<'a> {
+    pub(crate) name: &'a str,
+    pub(crate) span: &'a Range<usize>,
+    /// Whether the variable may be missing, since a `default` filter is applied.
+    pub(crate) optional: bool,
+}
+
+/// All references to template variables in `nodes`, in order of occurrence.
+pub(crate) fn variable_references(nodes: &[TemplateNode]) -> Vec<VariableReference<'_>> {
+    fn visit<'a>(
+        nodes: &'a [TemplateNode],
+        items: &mut Vec<&'a str>,
+        references: &mut Vec<VariableReference<'a>>,
+    ) {
+        for node in nodes {
+            let (name, span, optional) = match node {
+                TemplateNode::Text(_) => continue,
+                TemplateNode::Variable {
+                    name,
+                    filters,
+                    span,
+                } => (name, span, filters.iter().any(TemplateFilter::is_default)),
+                TemplateNode::If { name, span, .. } | TemplateNode::Each { name, span, .. } => {
+                    (name, span, false)
+                }
+            };
+            if !items.contains(&path_root(name)) {
+                references.push(VariableReference {
+                    name,
+                    span,
+                    optional,
+                });
+            }
+            match node {
+                TemplateNode::If {
+                    then, otherwise, ..
+                } => {
+                    visit(then, items, references);
+                    visit(otherwise, items, references);
+                }
+                TemplateNode::Each { item, body, .. } => {
+                    items.push(item);
+                    visit(body, items, references);
+                    items.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+    let mut references = Vec::new();
+    visit(nodes, &mut Vec::new(), &mut references);
+    references
+}
+
+/// Names of the variables used by `nodes`, without duplicates, in order of first use.
+pub(crate) fn variable_names(nodes: &[TemplateNode]) -> Vec<&str> {
+    let mut names = Vec::<&str>::new();
+    for reference in variable_references(nodes) {
+        let name = path_root(reference.name);
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+/// Fails with [`TemplateError::MissingVariables`] if a variable which is
+/// required by `nodes` has no value in `variables`.
+pub(crate) fn check_missing(
+    nodes: &[TemplateNode],
+    variables: &TemplateVariables,
+) -> Result<(), TemplateError> {
+    let mut missing = Vec::<String>::new();
+    for reference in variable_references(nodes) {
+        let name = path_root(reference.name);
+        if !reference.optional && !variables.contains(name) && !missing.iter().any(|m| m == name) {
+            missing.push(name.to_owned());
+        }
+    }
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(TemplateError::MissingVariables { names: missing })
+    }
+}
+
+/// Fails with [`TemplateError::UnknownVariables`] if `variables` has values not in `names`.
+pub(crate) fn check_unknown(
+    names: &[&str],
+    variables: &TemplateVariables,
+) -> Result<(), TemplateError> {
+    let unknown = variables
+        .names()
+        .filter(|name| !names.contains(name))
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+    if unknown.is_empty() {
+        Ok(())
+    } else {
+        Err(TemplateError::UnknownVariables { names: unknown })
+    }
+}
+
+/// Variables and the current `<#each>` items while rendering.
+struct Scope<'a> {
+    variables: &'a TemplateVariables,
+    items: Vec<(&'a str, &'a TemplateValue)>,
+}
+
+impl<'a> Scope<'a> {
+    fn lookup(&self, path: &str) -> Option<&'a TemplateValue> {
+        let mut segments = path.split('.');
+        let root = segments.next()?;
+        let mut value = self
+            .items
+            .iter()
+            .rev()
+            .find(|(item, _)| *item == root)
+            .map(|(_, value)| *value)
+            .or_else(|| self.variables.get(root))?;
+        for field in segments {
+            let TemplateValue::Map(fields) = value else {
+                return None;
+            };
+            value = fields.get(field)?;
+        }
+        Some(value)
+    }
+
+    fn require(&self, path: &str) -> Result<&'a TemplateValue, TemplateError> {
+        self.lookup(path)
+            .ok_or_else(|| TemplateError::MissingVariables {
+                names: vec![path.to_owned()],
+            })
+    }
+
+    fn render(
+        &mut self,
+        nodes: &'a [TemplateNode],
+        rendered: &mut String,
+    ) -> Result<(), TemplateError> {
+        for node in nodes {
+            match node {
+                TemplateNode::Text(text) => rendered.push_str(text),
+                TemplateNode::Variable { name, filters, .. } => {
+                    let mut value = self.lookup(name).cloned();
+                    for filter in filters {
+                        value = filter.apply(value)?;
+                    }
+                    let value = value.ok_or_else(|| TemplateError::MissingVariables {
+                        names: vec![name.clone()],
+                    })?;
+                    rendered.push_str(&value.to_string());
+                }
+                TemplateNode::If {
+                    name,
+                    negated,
+                    then,
+                    otherwise,
+                    ..
+                } => {
+                    if self.require(name)?.is_truthy() != *negated {
+                        self.render(then, rendered)?;
+                    } else {
+                        self.render(otherwise, rendered)?;
+                    }
+                }
+                TemplateNode::Each {
+                    name, item, body, ..
+                } => {
+                    let TemplateValue::List(values) = self.require(name)? else {
+                        return Err(TemplateError::NotAList { name: name.clone() });
+                    };
+                    for value in values {
+                        self.items.push((item, value));
+                        let result = self.render(body, rendered);
+                        self.items.pop();
+                        result?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Substitutes the values of `variables` in a single pass, so that values are
+/// never themselves interpreted as placeholders.
+pub(crate) fn render_nodes(
+    nodes: &[TemplateNode],
+    variables: &TemplateVariables,
+) -> Result<String, TemplateError> {
+    check_missing(nodes, variables)?;
+    let mut rendered = String::new();
+    Scope {
+        variables,
+        items: Vec::new(),
+    }
+    .render(nodes, &mut rendered)?;
+    Ok(rendered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variable(name: &str, span: Range<usize>) -> TemplateNode {
+        TemplateNode::Variable {
+            name: name.to_owned(),
+            filters: Vec::new(),
+            span,
+        }
+    }
+
+    fn render(source: &str, variables: &TemplateVariables) -> Result<String, TemplateError> {
+        render_nodes(&parse_template(source)?, variables)
+    }
+
+    #[test]
+    fn parse_placeholders_and_text() {
+        assert_eq!(
+            parse_template("Invoice <INV_NO> from <FROM_CO>").unwrap(),
+            vec![
+                TemplateNode::Text("Invoice ".to_owned()),
+                variable("INV_NO", 8..16),
+                TemplateNode::Text(" from ".to_owned()),
+                variable("FROM_CO", 22..31),
+            ]
+        );
+    }
+
+    #[test]
+    fn html_tags_are_text() {
+        assert_eq!(
+            parse_template("<b>Hi</b> <a href=\"x\"> <3 <UNTERMINATED").unwrap(),
+            vec![TemplateNode::Text(
+                "<b>Hi</b> <a href=\"x\"> <3 <UNTERMINATED".to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn escaped_placeholder_is_text() {
+        assert_eq!(
+            parse_template(r"Use \<INV_NO> for <WHAT>").unwrap(),
+            vec![
+                TemplateNode::Text("Use <INV_NO> for ".to_owned()),
+                variable("WHAT", 18..24),
+            ]
+        );
+    }
+
+    #[test]
+    fn values_are_not_rendered_recursively() {
+        let variables = TemplateVariables::from([("A", "<B>"), ("B", "b")]);
+        assert_eq!(render("<A> and <B>", &variables).unwrap(), "<B> and b");
+    }
+
+    #[test]
+    fn conditional() {
+        let source = "Hi<#if OVERDUE>, please pay<#else>, thanks</#if>.";
+        let overdue = TemplateVariables::from([("OVERDUE", true)]);
+        assert_eq!(render(source, &overdue).unwrap(), "Hi, please pay.");
+        let paid = TemplateVariables::from([("OVERDUE", false)]);
+        assert_eq!(render(source, &paid).unwrap(), "Hi, thanks.");
+        assert_eq!(render("<#if !OVERDUE>Paid</#if>", &paid).unwrap(), "Paid");
+    }
+
+    #[test]
+    fn loop_over_line_items() {
+        let item = |description: &str, amount: f64| {
+            TemplateVariables::new()
+                .with("DESCRIPTION", description)
+                .with("AMOUNT", amount)
+        };
+        let variables = TemplateVariables::new().with(
+            "ITEMS",
+            vec![item("Consulting", 1500.0), item("Hosting", 49.5)],
+        );
+        let source = "<#each ITEMS as ITEM>- <ITEM.DESCRIPTION | upper>: <ITEM.AMOUNT | currency:\"EUR\">\n</#each>";
+        assert_eq!(
+            render(source, &variables).unwrap(),
+            "- CONSULTING: 1500.00 EUR\n- HOSTING: 49.50 EUR\n"
+        );
+    }
+
+    #[test]
+    fn loop_over_non_list() {
+        let variables = TemplateVariables::from([("ITEMS", 1)]);
+        assert_eq!(
+            render("<#each ITEMS as ITEM><ITEM></#each>", &variables),
+            Err(TemplateError::NotAList {
+                name: "ITEMS".to_owned()
+            })
+        );
+    }
+
+    #[test]
+    fn default_filter_makes_variable_optional() {
+        let source = "Hi <NAME | default:\"there\">";
+        assert_eq!(
+            render(source, &TemplateVariables::new()).unwrap(),
+            "Hi there"
+        );
+        let nodes = parse_template(source).unwrap();
+        assert_eq!(variable_names(&nodes), ["NAME"]);
+    }
+
+    #[test]
+    fn item_references_are_not_variables() {
+        let nodes = parse_template("<#each ITEMS as ITEM><#if ITEM.PAID><ITEM.NAME></#if></#each>")
+            .unwrap();
+        assert_eq!(variable_names(&nodes), ["ITEMS"]);
+    }
+
+    #[test]
+    fn syntax_errors() {
+        let error = |source: &str| match parse_template(source) {
+            Err(TemplateError::Syntax {
+                line,
+                column,
+                reason,
+            }) => (line, column, reason),
+            other => panic!("expected syntax error, got {other:?}"),
+        };
+        assert_eq!(
+            error("Hi\n<#if OVERDUE>pay"),
+            (2, 1, "unclosed `<#if>`".to_owned())
+        );
+        assert_eq!(
+            error("</#each>"),
+            (1, 1, "unexpected `</#each>`".to_owned())
+        );
+        assert_eq!(
+            error("a <#loop ITEMS>"),
+            (1, 3, "invalid tag `<#loop ITEMS>`".to_owned())
+        );
+        assert_eq!(
+            error("<NAME | shout>"),
+            (1, 1, "unknown filter `shout`".to_owned())
+        );
+        assert_eq!(
+            error("<#each ITEMS as ITEM><#else></#each>"),
+            (1, 22, "unexpected `<#else>`".to_owned())
+        );
+    }
+}
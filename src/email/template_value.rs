@@ -0,0 +1,182 @@
+use std::fmt::{Display, Formatter};
+
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use crate::TemplateVariables;
+
+/// A typed value substituted for a placeholder in a template.
+///
+/// Lists can be iterated with `<#each ITEMS as ITEM>`, and the fields of maps
+/// are accessed with dots, e.g. `<ITEM.AMOUNT>`.
+///
+/// Deserialized untagged, in the order of the variants: strings in the
+/// `%Y-%m-%d` or RFC 3339 form become dates and date times, other strings texts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TemplateValue {
+    Boolean(bool),
+    Integer(i64),
+    Decimal(f64),
+    Date(NaiveDate),
+    DateTime(DateTime<FixedOffset>),
+    Text(String),
+    List(Vec<TemplateValue>),
+    Map(IndexMap<String, TemplateValue>),
+}
+
+impl TemplateValue {
+    /// Whether this value counts as true in `<#if NAME>`, which is `false` for
+    /// `false`, zero, and empty texts, lists and maps.
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Self::Boolean(value) => *value,
+            Self::Integer(value) => *value != 0,
+            Self::Decimal(value) => *value != 0.0,
+            Self::Text(value) => !value.is_empty(),
+            Self::List(values) => !values.is_empty(),
+            Self::Map(values) => !values.is_empty(),
+            Self::Date(_) | Self::DateTime(_) => true,
+        }
+    }
+
+    /// This value as a number, texts are parsed.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Integer(value) => Some(*value as f64),
+            Self::Decimal(value) => Some(*value),
+            Self::Text(value) => value.trim().parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+impl Display for TemplateValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Boolean(value) => write!(f, "{value}"),
+            Self::Integer(value) => write!(f, "{value}"),
+            Self::Decimal(value) => write!(f, "{value}"),
+            Self::Text(value) => write!(f, "{value}"),
+            Self::List(values) => {
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                Ok(())
+            }
+            Self::Map(values) => {
+                write!(f, "{{")?;
+                for (index, (key, value)) in values.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{key}: {value}")?;
+                }
+                write!(f, "}}")
+            }
+            Self::Date(value) => write!(f, "{}", value.format("%Y-%m-%d")),
+            Self::DateTime(value) => write!(f, "{}", value.to_rfc3339()),
+        }
+    }
+}
+
+impl From<String> for TemplateValue {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+
+impl From<&str> for TemplateValue {
+    fn from(value: &str) -> Self {
+        Self::Text(value.to_owned())
+    }
+}
+
+impl From<i64> for TemplateValue {
+    fn from(value: i64) -> Self {
+        Self::Integer(value)
+    }
+}
+
+impl From<i32> for TemplateValue {
+    fn from(value: i32) -> Self {
+        Self::Integer(value.into())
+    }
+}
+
+impl From<u32> for TemplateValue {
+    fn from(value: u32) -> Self {
+        Self::Integer(value.into())
+    }
+}
+
+impl From<f64> for TemplateValue {
+    fn from(value: f64) -> Self {
+        Self::Decimal(value)
+    }
+}
+
+impl From<bool> for TemplateValue {
+    fn from(value: bool) -> Self {
+        Self::Boolean(value)
+    }
+}
+
+impl From<NaiveDate> for TemplateValue {
+    fn from(value: NaiveDate) -> Self {
+        Self::Date(value)
+    }
+}
+
+impl From<DateTime<FixedOffset>> for TemplateValue {
+    fn from(value: DateTime<FixedOffset>) -> Self {
+        Self::DateTime(value)
+    }
+}
+
+impl<T: Into<TemplateValue>> From<Vec<T>> for TemplateValue {
+    fn from(values: Vec<T>) -> Self {
+        Self::List(values.into_iter().map(Into::into).collect())
+    }
+}
+
+impl From<TemplateVariables> for TemplateValue {
+    fn from(variables: TemplateVariables) -> Self {
+        Self::Map(variables.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_dates_before_texts() {
+        let values: Vec<TemplateValue> =
+            serde_json::from_str(r#"["2025-03-14", "2025-03-14T09:30:00+01:00", "Pi day"]"#)
+                .unwrap();
+        assert_eq!(
+            values,
+            vec![
+                TemplateValue::Date(NaiveDate::from_ymd_opt(2025, 3, 14).unwrap()),
+                TemplateValue::DateTime(
+                    DateTime::parse_from_rfc3339("2025-03-14T09:30:00+01:00").unwrap()
+                ),
+                TemplateValue::from("Pi day"),
+            ]
+        );
+    }
+
+    #[test]
+    fn json_roundtrip_dates() {
+        let value = TemplateValue::DateTime(
+            DateTime::parse_from_rfc3339("2025-03-14T09:30:00+01:00").unwrap(),
+        );
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(serde_json::from_str::<TemplateValue>(&json).unwrap(), value);
+    }
+}
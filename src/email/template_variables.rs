@@ -0,0 +1,98 @@
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use crate::TemplateValue;
+
+/// Values of the variables of a template, keyed by placeholder name without
+/// angle brackets, e.g. `"INV_NO"` for the placeholder `<INV_NO>`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TemplateVariables(IndexMap<String, TemplateValue>);
+
+impl TemplateVariables {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns these variables with `name` set to `value`.
+    pub fn with(mut self, name: impl Into<String>, value: impl Into<TemplateValue>) -> Self {
+        self.insert(name, value);
+        self
+    }
+
+    /// Sets `name` to `value`, returning the previous value, if any.
+    pub fn insert(
+        &mut self,
+        name: impl Into<String>,
+        value: impl Into<TemplateValue>,
+    ) -> Option<TemplateValue> {
+        self.0.insert(name.into(), value.into())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&TemplateValue> {
+        self.0.get(name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.0.contains_key(name)
+    }
+
+    /// Names of all variables, in insertion order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// These variables on top of `base`, i.e. a variable in both takes its
+    /// value from `self`.
+    pub(crate) fn layered_over(&self, base: &Self) -> Self {
+        let mut layered = base.0.clone();
+        layered.extend(
+            self.0
+                .iter()
+                .map(|(name, value)| (name.clone(), value.clone())),
+        );
+        Self(layered)
+    }
+
+    /// The variables named in `names`, e.g. those a template uses.
+    #[cfg(feature = "merge")]
+    pub(crate) fn restricted_to(&self, names: &indexmap::IndexSet<String>) -> Self {
+        Self(
+            self.0
+                .iter()
+                .filter(|(name, _)| names.contains(*name))
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect(),
+        )
+    }
+
+    pub(crate) fn into_inner(self) -> IndexMap<String, TemplateValue> {
+        self.0
+    }
+}
+
+impl<K: Into<String>, V: Into<TemplateValue>> FromIterator<(K, V)> for TemplateVariables {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self(
+            iter.into_iter()
+                .map(|(name, value)| (name.into(), value.into()))
+                .collect(),
+        )
+    }
+}
+
+impl<K: Into<String>, V: Into<TemplateValue>, const N: usize> From<[(K, V); N]>
+    for TemplateVariables
+{
+    fn from(variables: [(K, V); N]) -> Self {
+        variables.into_iter().collect()
+    }
+}
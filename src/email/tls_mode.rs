@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+
+/// How the connection to an SMTP server is secured.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, derive_more::Display, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum TlsMode {
+    /// TLS from the first byte ("SMTPS"), typically on port 465.
+    #[default]
+    #[display("Implicit TLS")]
+    Implicit,
+
+    /// Plaintext connection upgraded with a mandatory `STARTTLS`, typically on port 587.
+    #[display("STARTTLS")]
+    StartTls,
+
+    /// Upgrade with `STARTTLS` if the server offers it, otherwise stay plaintext.
+    #[display("Opportunistic STARTTLS")]
+    Opportunistic,
+
+    /// Plaintext connection without any TLS, typically on port 25.
+    #[display("No TLS")]
+    None,
+}
+
+impl TlsMode {
+    /// All TLS modes, in the order they are presented to users.
+    pub const ALL: [TlsMode; 4] = [
+        TlsMode::Implicit,
+        TlsMode::StartTls,
+        TlsMode::Opportunistic,
+        TlsMode::None,
+    ];
+
+    /// The port conventionally used together with this TLS mode.
+    pub fn default_port(&self) -> u16 {
+        match self {
+            TlsMode::Implicit => 465,
+            TlsMode::StartTls => 587,
+            TlsMode::Opportunistic | TlsMode::None => 25,
+        }
+    }
+
+    /// The TLS mode conventionally used on `port`, falling back to [`TlsMode::StartTls`]
+    /// for non-standard ports.
+    pub fn default_for_port(port: u16) -> Self {
+        match port {
+            465 => TlsMode::Implicit,
+            25 => TlsMode::Opportunistic,
+            _ => TlsMode::StartTls,
+        }
+    }
+}
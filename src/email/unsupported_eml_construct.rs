@@ -0,0 +1,54 @@
+use std::fmt::{Display, Formatter};
+
+/// Part of a parsed `.eml` message that [`crate::Email`] cannot represent, so
+/// it was dropped or simplified, see [`crate::ParsedEml::unsupported`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnsupportedEmlConstruct {
+    /// A header without counterpart in [`crate::Email`], e.g. `In-Reply-To`, was dropped.
+    Header { name: String },
+
+    /// An address group in `header`, its members were kept as plain addresses.
+    AddressGroup { header: String, name: String },
+
+    /// An address in `header` that is not a valid email address was dropped.
+    InvalidAddress { header: String, value: String },
+
+    /// `header` has more addresses than [`crate::Email`] holds, the address
+    /// `value` was dropped.
+    ExtraAddress { header: String, value: String },
+
+    /// A text or HTML body after the first one of its kind was dropped.
+    ExtraBody { mime_type: String },
+
+    /// An attachment identical to an earlier one, e.g. the same file attached
+    /// twice, was dropped, as [`crate::Email`] holds a set of attachments.
+    DuplicateAttachment { name: String },
+
+    /// A multipart other than `mixed`, `alternative` and `related`, e.g.
+    /// `multipart/signed`, its parts were read but the structure is lost.
+    MultipartSubtype { subtype: String },
+}
+
+impl Display for UnsupportedEmlConstruct {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Header { name } => write!(f, "dropped header {name}"),
+            Self::AddressGroup { header, name } => {
+                write!(f, "flattened address group '{name}' in {header}")
+            }
+            Self::InvalidAddress { header, value } => {
+                write!(f, "dropped invalid address '{value}' in {header}")
+            }
+            Self::ExtraAddress { header, value } => {
+                write!(f, "dropped extra address '{value}' in {header}")
+            }
+            Self::ExtraBody { mime_type } => write!(f, "dropped extra {mime_type} body"),
+            Self::DuplicateAttachment { name } => {
+                write!(f, "dropped duplicate attachment '{name}'")
+            }
+            Self::MultipartSubtype { subtype } => {
+                write!(f, "flattened multipart/{subtype}")
+            }
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use aes_gcm::{
     Key,
-    aead::{Aead, AeadCore, KeyInit, OsRng},
+    aead::{Aead, AeadCore, KeyInit, OsRng, Payload},
 };
 
 use super::{AesGcmSealedBox, AesNonce, CryptoError, EncryptionKey, Result};
@@ -16,17 +16,23 @@ impl AesGcm256 {
     /// # Arguments
     /// * `plaintext` - The data to encrypt.
     /// * `encryption_key` - The key used for encryption, must be 32 bytes long.
+    /// * `aad` - Associated data which is authenticated but not encrypted.
     ///
     /// # Returns
     /// An `AesGcmSealedBox` containing the encrypted data and nonce.
     fn _seal(
         plaintext: impl AsRef<[u8]>,
         encryption_key: Key<aes_gcm::Aes256Gcm>,
+        aad: &[u8],
     ) -> AesGcmSealedBox {
         let cipher = aes_gcm::Aes256Gcm::new(&encryption_key);
         let nonce = aes_gcm::Aes256Gcm::generate_nonce(&mut OsRng);
+        let payload = Payload {
+            msg: plaintext.as_ref(),
+            aad,
+        };
         let cipher_text = cipher
-            .encrypt(&nonce, plaintext.as_ref())
+            .encrypt(&nonce, payload)
             .expect("AES encrypt never fails for valid nonce.");
         let nonce = AesNonce::try_from(nonce.as_slice()).unwrap();
 
@@ -41,14 +47,19 @@ impl AesGcm256 {
     /// # Arguments
     /// * `sealed_box` - The sealed box containing the encrypted data and nonce.
     /// * `decryption_key` - The key used for decryption, must be 32 bytes long.
+    /// * `aad` - Associated data which must equal the one used when sealing.
     fn _open(
         sealed_box: AesGcmSealedBox,
         decryption_key: Key<aes_gcm::Aes256Gcm>,
+        aad: &[u8],
     ) -> Result<Vec<u8>> {
         let cipher = aes_gcm::Aes256Gcm::new(&decryption_key);
-        let cipher_text = sealed_box.cipher_text();
+        let payload = Payload {
+            msg: sealed_box.cipher_text(),
+            aad,
+        };
         cipher
-            .decrypt(sealed_box.nonce().into(), cipher_text.as_ref())
+            .decrypt(sealed_box.nonce().into(), payload)
             .map_err(CryptoError::aes_decryption_failed)
     }
 
@@ -72,7 +83,32 @@ impl AesGcm256 {
     /// assert_eq!(decrypted, secret.as_bytes());
     /// ```
     pub fn seal(plaintext: impl AsRef<[u8]>, encryption_key: EncryptionKey) -> AesGcmSealedBox {
-        Self::_seal(plaintext, encryption_key.into())
+        Self::_seal(plaintext, encryption_key.into(), &[])
+    }
+
+    /// Seals the plaintext like [`AesGcm256::seal`], additionally authenticating
+    /// the associated data `aad`, which is not encrypted nor stored in the sealed box.
+    /// # Arguments
+    /// * `plaintext` - The data to encrypt.
+    /// * `encryption_key` - The key used for encryption, must be 32 bytes long.
+    /// * `aad` - Associated data, which must be passed to [`AesGcm256::open_with_aad`] again.
+    ///
+    /// # Examples
+    /// ```
+    /// extern crate mejla;
+    /// use mejla::*;
+    /// let encryption_key = EncryptionKey([0xabu8; 32]);
+    /// let encrypted = AesGcm256::seal_with_aad(b"secret", encryption_key.clone(), b"alice");
+    /// assert!(AesGcm256::open_with_aad(encrypted.clone(), encryption_key.clone(), b"bob").is_err());
+    /// let decrypted = AesGcm256::open_with_aad(encrypted, encryption_key, b"alice").unwrap();
+    /// assert_eq!(decrypted, b"secret");
+    /// ```
+    pub fn seal_with_aad(
+        plaintext: impl AsRef<[u8]>,
+        encryption_key: EncryptionKey,
+        aad: impl AsRef<[u8]>,
+    ) -> AesGcmSealedBox {
+        Self::_seal(plaintext, encryption_key.into(), aad.as_ref())
     }
 
     /// Opens the sealed box by decrypting the cipher text using the provided decryption key.
@@ -95,7 +131,21 @@ impl AesGcm256 {
     /// assert_eq!(decrypted, secret.as_bytes());
     /// ```
     pub fn open(sealed_box: AesGcmSealedBox, decryption_key: EncryptionKey) -> Result<Vec<u8>> {
-        Self::_open(sealed_box, decryption_key.into())
+        Self::_open(sealed_box, decryption_key.into(), &[])
+    }
+
+    /// Opens a sealed box created by [`AesGcm256::seal_with_aad`], fails if
+    /// `aad` differs from the associated data used when sealing.
+    /// # Arguments
+    /// * `sealed_box` - The sealed box containing the encrypted data and nonce.
+    /// * `decryption_key` - The key used for decryption, must be 32 bytes long.
+    /// * `aad` - Associated data used when sealing.
+    pub fn open_with_aad(
+        sealed_box: AesGcmSealedBox,
+        decryption_key: EncryptionKey,
+        aad: impl AsRef<[u8]>,
+    ) -> Result<Vec<u8>> {
+        Self::_open(sealed_box, decryption_key.into(), aad.as_ref())
     }
 }
 
@@ -127,6 +177,18 @@ mod tests {
         assert_eq!(plaintext, decrypted_str);
     }
 
+    #[test]
+    fn test_roundtrip_with_aad() {
+        let encryption_key = sample_encryption_key();
+        let sealed = AesGcm256::seal_with_aad("so super secret", encryption_key.clone(), "aad");
+        assert_eq!(
+            AesGcm256::open(sealed.clone(), encryption_key.clone()),
+            Err(CryptoError::AesDecryptionFailed)
+        );
+        let decrypted = AesGcm256::open_with_aad(sealed, encryption_key, "aad").unwrap();
+        assert_eq!(decrypted, b"so super secret");
+    }
+
     #[test]
     fn test_fail() {
         assert_eq!(
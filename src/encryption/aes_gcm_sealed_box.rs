@@ -6,7 +6,7 @@ use super::{CryptoError, Result};
 
 pub type AesNonce = [u8; 12];
 
-#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Hash, Builder, Getters)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash, Builder, Getters)]
 pub struct AesGcmSealedBox {
     /// Nonce is 12 bytes
     #[getset(get = "pub")]
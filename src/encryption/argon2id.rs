@@ -0,0 +1,156 @@
+use argon2::{Algorithm, Argon2, Params, Version};
+use bon::Builder;
+use getset::CopyGetters;
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Serialize};
+
+use super::{CryptoError, EncryptionKey, Result, Salt};
+
+/// Tunable cost parameters of Argon2id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Builder, CopyGetters, Serialize, Deserialize)]
+pub struct Argon2idParams {
+    /// Memory cost in KiB.
+    #[getset(get_copy = "pub")]
+    memory_kib: u32,
+
+    /// Number of passes over the memory.
+    #[getset(get_copy = "pub")]
+    iterations: u32,
+
+    /// Degree of parallelism (lanes).
+    #[getset(get_copy = "pub")]
+    parallelism: u32,
+}
+
+impl Argon2idParams {
+    /// Upper bound of [`Argon2idParams::memory_kib`], 4 GiB.
+    pub const MAX_MEMORY_KIB: u32 = 4 * 1024 * 1024;
+
+    /// Upper bound of [`Argon2idParams::iterations`].
+    pub const MAX_ITERATIONS: u32 = 64;
+
+    /// Upper bound of [`Argon2idParams::parallelism`].
+    pub const MAX_PARALLELISM: u32 = 64;
+
+    /// Checks that the parameters are within the upper bounds, so that
+    /// parameters read from a corrupt or tampered file cannot make key
+    /// derivation allocate an unbounded amount of memory or run for hours.
+    pub fn check_bounds(&self) -> Result<()> {
+        if self.memory_kib > Self::MAX_MEMORY_KIB
+            || self.iterations > Self::MAX_ITERATIONS
+            || self.parallelism > Self::MAX_PARALLELISM
+        {
+            return Err(CryptoError::KeyDerivationParamsOutOfRange {
+                memory_kib: self.memory_kib,
+                iterations: self.iterations,
+                parallelism: self.parallelism,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Default for Argon2idParams {
+    /// The OWASP recommended minimum: 19 MiB of memory, 2 iterations, 1 lane.
+    fn default() -> Self {
+        Self {
+            memory_kib: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+/// A memory-hard password based key-derivation helper using Argon2id.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct Argon2id;
+
+impl Argon2id {
+    pub fn derive_key(
+        password: impl AsRef<[u8]>,
+        salt: &Salt,
+        params: &Argon2idParams,
+    ) -> Result<EncryptionKey> {
+        params.check_bounds()?;
+        let params = Params::new(
+            params.memory_kib,
+            params.iterations,
+            params.parallelism,
+            Some(32),
+        )
+        .map_err(CryptoError::invalid_key_derivation_params)?;
+        let mut okm = [0u8; 32];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(password.as_ref(), salt.as_ref(), &mut okm)
+            .map_err(CryptoError::invalid_key_derivation_params)?;
+        Ok(okm.into())
+    }
+
+    pub fn derive_key_from(
+        password: SecretString,
+        salt: &Salt,
+        params: &Argon2idParams,
+    ) -> Result<EncryptionKey> {
+        Self::derive_key(password.expose_secret(), salt, params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex::encode as hex_encode;
+
+    fn cheap_params() -> Argon2idParams {
+        Argon2idParams::builder()
+            .memory_kib(64)
+            .iterations(1)
+            .parallelism(1)
+            .build()
+    }
+
+    #[test]
+    fn test_kdf() {
+        let salt = Salt::from([0u8; 16]);
+        let derived = Argon2id::derive_key("open sesame", &salt, &cheap_params()).unwrap();
+        assert_eq!(
+            hex_encode(*derived),
+            "44670fdb73bfdda45f0aff6c9fba760b745d2a100e7e97cd234b38c7daeab917"
+        );
+        assert_ne!(
+            derived,
+            Argon2id::derive_key("open sesame!", &salt, &cheap_params()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_params_out_of_range() {
+        let params = Argon2idParams::builder()
+            .memory_kib(u32::MAX)
+            .iterations(1)
+            .parallelism(1)
+            .build();
+        assert_eq!(
+            Argon2id::derive_key("open sesame", &Salt::sample(), &params),
+            Err(CryptoError::KeyDerivationParamsOutOfRange {
+                memory_kib: u32::MAX,
+                iterations: 1,
+                parallelism: 1
+            })
+        );
+        assert_eq!(Argon2idParams::default().check_bounds(), Ok(()));
+    }
+
+    #[test]
+    fn test_invalid_params() {
+        let params = Argon2idParams::builder()
+            .memory_kib(64)
+            .iterations(0)
+            .parallelism(1)
+            .build();
+        let result = Argon2id::derive_key("open sesame", &Salt::sample(), &params);
+        assert!(matches!(
+            result,
+            Err(CryptoError::InvalidKeyDerivationParams { .. })
+        ));
+    }
+}
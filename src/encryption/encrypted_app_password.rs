@@ -3,8 +3,20 @@ use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 use zeroize::{Zeroize, ZeroizeOnDrop};
 
-use super::{AesGcm256, AesGcmSealedBox, CryptoError, EncryptionKey, PbHkdfSha256, Result, Salt};
+use super::{
+    AesGcm256, CryptoError, EncryptionKey, Envelope, EnvelopeVersion, KeyDerivation, Result, Salt,
+};
 
+/// An encrypted SMTP app password, stored as the hex of an [`Envelope`]
+/// describing the key derivation and cipher used.
+///
+/// The cipher text is bound to associated data, typically identifying the
+/// sender and SMTP server, so that it cannot be copied into other settings.
+/// Only legacy envelopes, written before the binding was introduced, are not
+/// bound and decrypt regardless of the associated data.
+///
+/// The envelope is only parsed when decrypting, so settings containing an
+/// envelope of an unsupported version can still be loaded.
 #[serde_as]
 #[derive(
     Clone,
@@ -20,37 +32,113 @@ use super::{AesGcm256, AesGcmSealedBox, CryptoError, EncryptionKey, PbHkdfSha256
 )]
 #[display("{}", hex::encode(&self.0))]
 #[debug("{}", hex::encode(&self.0))]
-#[serde(transparent)]
 pub struct EncryptedAppPassword(#[serde_as(as = "serde_with::hex::Hex")] Vec<u8>);
 
 impl EncryptedAppPassword {
+    /// Encrypts `app_password` with a key derived from `encryption_password`
+    /// using the default [`KeyDerivation`] (Argon2id), bound to `associated_data`.
     pub fn new_by_deriving_and_encrypting(
         app_password: SecretString,
         encryption_password: SecretString,
         salt: &Salt,
+        associated_data: &[u8],
     ) -> Self {
-        let encryption_key = PbHkdfSha256::derive_key_from(encryption_password, salt);
-        Self::new_by_encrypting(app_password, encryption_key)
+        Self::new_by_deriving_and_encrypting_with(
+            app_password,
+            encryption_password,
+            salt,
+            KeyDerivation::default(),
+            associated_data,
+        )
+        .expect("default key derivation parameters are valid")
+    }
+
+    /// Encrypts `app_password` with a key derived from `encryption_password`
+    /// using `key_derivation`, bound to `associated_data`.
+    pub fn new_by_deriving_and_encrypting_with(
+        app_password: SecretString,
+        encryption_password: SecretString,
+        salt: &Salt,
+        key_derivation: KeyDerivation,
+        associated_data: &[u8],
+    ) -> Result<Self> {
+        let encryption_key = key_derivation.derive_key_from(encryption_password, salt)?;
+        Ok(Self::new_by_encrypting(
+            app_password,
+            encryption_key,
+            key_derivation,
+            associated_data,
+        ))
+    }
+
+    /// Encrypts `app_password` with `encryption_key`, which was derived using
+    /// `key_derivation`, bound to `associated_data`.
+    pub fn new_by_encrypting(
+        app_password: SecretString,
+        encryption_key: EncryptionKey,
+        key_derivation: KeyDerivation,
+        associated_data: &[u8],
+    ) -> Self {
+        let sealed_box = AesGcm256::seal_with_aad(
+            app_password.expose_secret().as_bytes(),
+            encryption_key,
+            associated_data,
+        );
+        Self(Envelope::new(key_derivation, associated_data, sealed_box).to_bytes())
+    }
+
+    /// Parses the envelope holding the encrypted app password.
+    pub fn envelope(&self) -> Result<Envelope> {
+        Envelope::try_from(self.0.as_slice())
     }
 
-    pub fn new_by_encrypting(app_password: SecretString, encryption_key: EncryptionKey) -> Self {
-        let sealed_box = AesGcm256::seal(app_password.expose_secret().as_bytes(), encryption_key);
-        let combined = sealed_box.combined();
-        Self(combined)
+    /// The key derivation used to derive the encryption key from the encryption password.
+    pub fn key_derivation(&self) -> Result<KeyDerivation> {
+        self.envelope().map(|envelope| *envelope.key_derivation())
     }
 
+    /// Derives the key from `encryption_password` and decrypts, failing with
+    /// [`CryptoError::AssociatedDataMismatch`] if the app password is bound to
+    /// other `associated_data`.
     pub fn derive_and_decrypt(
         &self,
         encryption_password: SecretString,
         salt: &Salt,
+        associated_data: &[u8],
+    ) -> Result<SecretString> {
+        let envelope = self.envelope()?;
+        envelope.verify_associated_data(associated_data)?;
+        let encryption_key = envelope
+            .key_derivation()
+            .derive_key_from(encryption_password, salt)?;
+        Self::open(envelope, encryption_key, associated_data)
+    }
+
+    /// Decrypts using `encryption_key`, failing with [`CryptoError::AssociatedDataMismatch`]
+    /// if the app password is bound to other `associated_data`.
+    pub fn decrypt(
+        &self,
+        encryption_key: EncryptionKey,
+        associated_data: &[u8],
     ) -> Result<SecretString> {
-        let encryption_key = PbHkdfSha256::derive_key_from(encryption_password, salt);
-        self.decrypt(encryption_key)
+        let envelope = self.envelope()?;
+        envelope.verify_associated_data(associated_data)?;
+        Self::open(envelope, encryption_key, associated_data)
     }
 
-    pub fn decrypt(&self, encryption_key: EncryptionKey) -> Result<SecretString> {
-        let sealed_box = AesGcmSealedBox::try_from(self.0.as_slice())?;
-        let decrypted = AesGcm256::open(sealed_box, encryption_key)?;
+    fn open(
+        envelope: Envelope,
+        encryption_key: EncryptionKey,
+        associated_data: &[u8],
+    ) -> Result<SecretString> {
+        let decrypted = match envelope.version() {
+            EnvelopeVersion::Legacy => AesGcm256::open(envelope.into_sealed_box(), encryption_key)?,
+            EnvelopeVersion::V2 => AesGcm256::open_with_aad(
+                envelope.into_sealed_box(),
+                encryption_key,
+                associated_data,
+            )?,
+        };
         String::from_utf8(decrypted)
             .map_err(|_| CryptoError::InvalidUtf8)
             .map(SecretString::from)
@@ -78,6 +166,9 @@ impl EncryptedAppPassword {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{Argon2idParams, PbHkdfSha256};
+
+    const AAD: &[u8] = b"alice@example.com smtp.gmail.com";
 
     #[test]
     fn test_encrypted_app_password() {
@@ -88,10 +179,82 @@ mod tests {
             app_password.clone(),
             encryption_pwd.clone(),
             &salt,
+            AAD,
         );
-        let decrypted = encrypted.derive_and_decrypt(encryption_pwd, &salt).unwrap();
+        let decrypted = encrypted
+            .derive_and_decrypt(encryption_pwd, &salt, AAD)
+            .unwrap();
 
         assert_eq!(decrypted.expose_secret(), app_password.expose_secret());
+        assert_eq!(encrypted.key_derivation(), Ok(KeyDerivation::default()));
+        assert_eq!(encrypted.envelope().unwrap().version(), EnvelopeVersion::V2);
+    }
+
+    #[test]
+    fn test_decrypt_with_other_associated_data_fails() {
+        let encryption_pwd = SecretString::from("open sesame");
+        let salt = Salt::sample();
+        let encrypted = EncryptedAppPassword::new_by_encrypting(
+            SecretString::from("app password"),
+            PbHkdfSha256::derive_key_from(encryption_pwd.clone(), &salt),
+            KeyDerivation::HkdfSha256,
+            AAD,
+        );
+        let result = encrypted.derive_and_decrypt(
+            encryption_pwd,
+            &salt,
+            b"mallory@example.com smtp.gmail.com",
+        );
+        assert!(matches!(result, Err(CryptoError::AssociatedDataMismatch)));
+    }
+
+    #[test]
+    fn test_decrypt_legacy_hkdf() {
+        let encrypted: EncryptedAppPassword = serde_json::from_str(
+            r#""3219e571fbb18265b1fb3f36a75c8e7ef4feef52892a5be25d0b9a92154c5de6456cdfe66aa70070""#,
+        )
+        .unwrap();
+        assert_eq!(encrypted, EncryptedAppPassword::sample());
+        assert_eq!(encrypted.key_derivation(), Ok(KeyDerivation::HkdfSha256));
+        assert_eq!(
+            encrypted.envelope().unwrap().version(),
+            EnvelopeVersion::Legacy
+        );
+        let decrypted = encrypted
+            .derive_and_decrypt(
+                SecretString::from("encryption password"),
+                &Salt::sample(),
+                AAD,
+            )
+            .unwrap();
+        assert_eq!(decrypted.expose_secret(), "super secret");
+    }
+
+    #[test]
+    fn test_serde_roundtrip_argon2id() {
+        let key_derivation = KeyDerivation::Argon2id(
+            Argon2idParams::builder()
+                .memory_kib(64)
+                .iterations(1)
+                .parallelism(1)
+                .build(),
+        );
+        let encrypted = EncryptedAppPassword::new_by_deriving_and_encrypting_with(
+            SecretString::from("my_secret_app_password"),
+            SecretString::from("open sesame"),
+            &Salt::sample(),
+            key_derivation,
+            AAD,
+        )
+        .unwrap();
+        let json = serde_json::to_string(&encrypted).unwrap();
+        assert!(json.starts_with(r#""4d4a4c410201"#));
+        let deserialized: EncryptedAppPassword = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, encrypted);
+        let decrypted = deserialized
+            .derive_and_decrypt(SecretString::from("open sesame"), &Salt::sample(), AAD)
+            .unwrap();
+        assert_eq!(decrypted.expose_secret(), "my_secret_app_password");
     }
 
     #[test]
@@ -103,7 +266,44 @@ mod tests {
         let sealed_box = AesGcm256::seal(&invalid_utf8_bytes, encryption_key);
         let malformed_encrypted = EncryptedAppPassword(sealed_box.combined());
         let decryption_key = PbHkdfSha256::derive_key_from(SecretString::from("key"), &salt);
-        let result = malformed_encrypted.decrypt(decryption_key);
+        let result = malformed_encrypted.decrypt(decryption_key, AAD);
         assert!(matches!(result, Err(CryptoError::InvalidUtf8)));
     }
+
+    #[test]
+    fn test_only_legacy_ignores_associated_data() {
+        let encryption_pwd = SecretString::from("encryption password");
+        let legacy = EncryptedAppPassword::sample();
+        assert!(
+            legacy
+                .derive_and_decrypt(encryption_pwd.clone(), &Salt::sample(), b"anything")
+                .is_ok()
+        );
+
+        let bound = EncryptedAppPassword::new_by_deriving_and_encrypting_with(
+            SecretString::from("super secret"),
+            encryption_pwd.clone(),
+            &Salt::sample(),
+            KeyDerivation::HkdfSha256,
+            AAD,
+        )
+        .unwrap();
+        assert!(bound.envelope().unwrap().is_bound());
+        assert_eq!(
+            bound
+                .derive_and_decrypt(encryption_pwd, &Salt::sample(), b"anything")
+                .map(|_| ()),
+            Err(CryptoError::AssociatedDataMismatch)
+        );
+    }
+
+    #[test]
+    fn test_decrypt_unsupported_version() {
+        let encrypted: EncryptedAppPassword = serde_json::from_str(r#""4d4a4c4109""#).unwrap();
+        let result = encrypted.derive_and_decrypt(SecretString::from("key"), &Salt::sample(), AAD);
+        assert!(matches!(
+            result,
+            Err(CryptoError::UnsupportedEnvelopeVersion { version: 9 })
+        ));
+    }
 }
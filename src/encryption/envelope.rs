@@ -0,0 +1,336 @@
+use sha2::{Digest, Sha256};
+
+use super::{AesGcmSealedBox, Argon2idParams, CryptoError, KeyDerivation, Result};
+
+/// Version of the [`Envelope`] binary format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EnvelopeVersion {
+    /// Unversioned `nonce || cipher_text` blobs written by earlier versions,
+    /// always HKDF-SHA256 and AES-GCM-256.
+    Legacy,
+    /// Self-describing envelope with KDF id and parameters and cipher id,
+    /// the cipher text is bound to associated data, whose SHA-256 digest is
+    /// stored in the envelope.
+    V2,
+}
+
+/// Symmetric cipher used to seal an [`Envelope`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Cipher {
+    Aes256Gcm,
+}
+
+impl Cipher {
+    const AES_256_GCM_ID: u8 = 1;
+
+    fn id(&self) -> u8 {
+        match self {
+            Self::Aes256Gcm => Self::AES_256_GCM_ID,
+        }
+    }
+
+    fn from_id(id: u8) -> Result<Self> {
+        match id {
+            Self::AES_256_GCM_ID => Ok(Self::Aes256Gcm),
+            _ => Err(CryptoError::UnsupportedCipher { id }),
+        }
+    }
+}
+
+/// A versioned, self-describing container of an encrypted secret.
+///
+/// Binary layout of [`EnvelopeVersion::V2`]:
+/// ```text
+/// magic "MJLA" (4) || version (1) || kdf id (1) || kdf params (0 or 12) || cipher id (1) || aad digest (32) || nonce (12) || cipher text
+/// ```
+/// where the Argon2id params are memory KiB, iterations and parallelism as
+/// big-endian `u32`s, which must be within [`Argon2idParams::check_bounds`].
+/// Bytes not starting with the magic are read as legacy `nonce || cipher_text` blobs.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Envelope {
+    version: EnvelopeVersion,
+    key_derivation: KeyDerivation,
+    cipher: Cipher,
+    associated_data_digest: Option<[u8; 32]>,
+    sealed_box: AesGcmSealedBox,
+}
+
+impl Envelope {
+    const MAGIC: &'static [u8; 4] = b"MJLA";
+    const V2: u8 = 2;
+    const KDF_HKDF_SHA256_ID: u8 = 0;
+    const KDF_ARGON2ID_ID: u8 = 1;
+
+    /// A new envelope of the latest version, for a `sealed_box` sealed with `associated_data`.
+    pub fn new(
+        key_derivation: KeyDerivation,
+        associated_data: &[u8],
+        sealed_box: AesGcmSealedBox,
+    ) -> Self {
+        Self {
+            version: EnvelopeVersion::V2,
+            key_derivation,
+            cipher: Cipher::Aes256Gcm,
+            associated_data_digest: Some(Self::digest(associated_data)),
+            sealed_box,
+        }
+    }
+
+    fn digest(associated_data: &[u8]) -> [u8; 32] {
+        Sha256::digest(associated_data).into()
+    }
+
+    /// Whether the sealed box is bound to associated data, all but legacy envelopes are.
+    pub fn is_bound(&self) -> bool {
+        self.associated_data_digest.is_some()
+    }
+
+    /// Checks that `associated_data` is the one the sealed box was bound to,
+    /// trivially true for envelopes which are not bound.
+    pub fn verify_associated_data(&self, associated_data: &[u8]) -> Result<()> {
+        match self.associated_data_digest {
+            Some(digest) if digest != Self::digest(associated_data) => {
+                Err(CryptoError::AssociatedDataMismatch)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn version(&self) -> EnvelopeVersion {
+        self.version
+    }
+
+    pub fn key_derivation(&self) -> &KeyDerivation {
+        &self.key_derivation
+    }
+
+    pub fn cipher(&self) -> Cipher {
+        self.cipher
+    }
+
+    pub fn sealed_box(&self) -> &AesGcmSealedBox {
+        &self.sealed_box
+    }
+
+    pub fn into_sealed_box(self) -> AesGcmSealedBox {
+        self.sealed_box
+    }
+
+    /// Serializes the envelope, legacy envelopes are written in the legacy format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let sealed_box = self.sealed_box.clone().combined();
+        let version = match self.version {
+            EnvelopeVersion::Legacy => return sealed_box,
+            EnvelopeVersion::V2 => Self::V2,
+        };
+        let mut bytes = Self::MAGIC.to_vec();
+        bytes.push(version);
+        match &self.key_derivation {
+            KeyDerivation::HkdfSha256 => bytes.push(Self::KDF_HKDF_SHA256_ID),
+            KeyDerivation::Argon2id(params) => {
+                bytes.push(Self::KDF_ARGON2ID_ID);
+                bytes.extend_from_slice(&params.memory_kib().to_be_bytes());
+                bytes.extend_from_slice(&params.iterations().to_be_bytes());
+                bytes.extend_from_slice(&params.parallelism().to_be_bytes());
+            }
+        }
+        bytes.push(self.cipher.id());
+        if let Some(digest) = &self.associated_data_digest {
+            bytes.extend_from_slice(digest);
+        }
+        bytes.extend(sealed_box);
+        bytes
+    }
+
+    fn legacy(bytes: &[u8]) -> Result<Self> {
+        Ok(Self {
+            version: EnvelopeVersion::Legacy,
+            key_derivation: KeyDerivation::HkdfSha256,
+            cipher: Cipher::Aes256Gcm,
+            associated_data_digest: None,
+            sealed_box: AesGcmSealedBox::try_from(bytes)?,
+        })
+    }
+
+    /// Parses the part following the version byte of a V2 envelope.
+    fn parse_v2(reader: &mut Reader<'_>) -> Result<Self> {
+        let key_derivation = match reader.u8()? {
+            Self::KDF_HKDF_SHA256_ID => KeyDerivation::HkdfSha256,
+            Self::KDF_ARGON2ID_ID => {
+                let params = Argon2idParams::builder()
+                    .memory_kib(reader.u32()?)
+                    .iterations(reader.u32()?)
+                    .parallelism(reader.u32()?)
+                    .build();
+                params.check_bounds()?;
+                KeyDerivation::Argon2id(params)
+            }
+            id => return Err(CryptoError::UnsupportedKeyDerivation { id }),
+        };
+        let cipher = Cipher::from_id(reader.u8()?)?;
+        let associated_data_digest = Some(reader.take::<32>()?);
+        let sealed_box = AesGcmSealedBox::try_from(reader.rest())?;
+        Ok(Self {
+            version: EnvelopeVersion::V2,
+            key_derivation,
+            cipher,
+            associated_data_digest,
+            sealed_box,
+        })
+    }
+}
+
+impl TryFrom<&[u8]> for Envelope {
+    type Error = CryptoError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self> {
+        if !bytes.starts_with(Self::MAGIC) {
+            return Self::legacy(bytes);
+        }
+        let mut reader = Reader::new(bytes);
+        reader.take::<4>()?;
+        match reader.u8()? {
+            Self::V2 => Self::parse_v2(&mut reader),
+            version => Err(CryptoError::UnsupportedEnvelopeVersion { version }),
+        }
+    }
+}
+
+/// Reads big-endian integers from the front of a byte slice.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, offset: 0 }
+    }
+
+    fn take<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let end = self.offset + N;
+        let bytes =
+            self.bytes
+                .get(self.offset..end)
+                .ok_or(CryptoError::InvalidEnvelopeTooShort {
+                    expected_at_least: end,
+                    found: self.bytes.len(),
+                })?;
+        self.offset = end;
+        Ok(bytes.try_into().expect("slice has length N"))
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        self.take::<1>().map(|[byte]| byte)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        self.take::<4>().map(u32::from_be_bytes)
+    }
+
+    fn rest(&self) -> &'a [u8] {
+        &self.bytes[self.offset..]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sealed_box() -> AesGcmSealedBox {
+        AesGcmSealedBox::builder()
+            .nonce([0xab; 12])
+            .cipher_text(vec![0xcd; 20])
+            .build()
+    }
+
+    #[test]
+    fn roundtrip_v2_argon2id() {
+        let envelope = Envelope::new(KeyDerivation::default(), b"aad", sealed_box());
+        let bytes = envelope.to_bytes();
+        assert!(bytes.starts_with(b"MJLA\x02\x01"));
+        assert_eq!(Envelope::try_from(bytes.as_slice()), Ok(envelope));
+    }
+
+    #[test]
+    fn roundtrip_v2_hkdf() {
+        let envelope = Envelope::new(KeyDerivation::HkdfSha256, b"aad", sealed_box());
+        let bytes = envelope.to_bytes();
+        assert_eq!(&bytes[..7], b"MJLA\x02\x00\x01");
+        assert_eq!(Envelope::try_from(bytes.as_slice()), Ok(envelope));
+    }
+
+    #[test]
+    fn verify_associated_data() {
+        let envelope = Envelope::new(KeyDerivation::HkdfSha256, b"aad", sealed_box());
+        assert_eq!(envelope.verify_associated_data(b"aad"), Ok(()));
+        assert_eq!(
+            envelope.verify_associated_data(b"other"),
+            Err(CryptoError::AssociatedDataMismatch)
+        );
+    }
+
+    #[test]
+    fn legacy_blob() {
+        let bytes = sealed_box().combined();
+        let envelope = Envelope::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(envelope.version(), EnvelopeVersion::Legacy);
+        assert_eq!(envelope.key_derivation(), &KeyDerivation::HkdfSha256);
+        assert_eq!(envelope.verify_associated_data(b"anything"), Ok(()));
+        assert_eq!(envelope.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn argon2id_params_out_of_range() {
+        let mut bytes = b"MJLA\x02\x01".to_vec();
+        bytes.extend_from_slice(&u32::MAX.to_be_bytes());
+        bytes.extend_from_slice(&2u32.to_be_bytes());
+        bytes.extend_from_slice(&1u32.to_be_bytes());
+        bytes.push(1);
+        bytes.extend_from_slice(&[0; 32]);
+        bytes.extend(sealed_box().combined());
+        assert_eq!(
+            Envelope::try_from(bytes.as_slice()),
+            Err(CryptoError::KeyDerivationParamsOutOfRange {
+                memory_kib: u32::MAX,
+                iterations: 2,
+                parallelism: 1
+            })
+        );
+    }
+
+    #[test]
+    fn unsupported_version() {
+        assert_eq!(
+            Envelope::try_from(&b"MJLA\x09"[..]),
+            Err(CryptoError::UnsupportedEnvelopeVersion { version: 9 })
+        );
+    }
+
+    #[test]
+    fn unsupported_kdf() {
+        assert_eq!(
+            Envelope::try_from(&b"MJLA\x02\x07"[..]),
+            Err(CryptoError::UnsupportedKeyDerivation { id: 7 })
+        );
+    }
+
+    #[test]
+    fn unsupported_cipher() {
+        assert_eq!(
+            Envelope::try_from(&b"MJLA\x02\x00\x05"[..]),
+            Err(CryptoError::UnsupportedCipher { id: 5 })
+        );
+    }
+
+    #[test]
+    fn truncated_kdf_params() {
+        assert_eq!(
+            Envelope::try_from(&b"MJLA\x02\x01\x00\x00"[..]),
+            Err(CryptoError::InvalidEnvelopeTooShort {
+                expected_at_least: 10,
+                found: 8
+            })
+        );
+    }
+}
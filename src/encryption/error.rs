@@ -10,12 +10,41 @@ pub enum CryptoError {
         expected_at_least: usize,
         found: usize,
     },
+    InvalidKeyDerivationParams {
+        reason: String,
+    },
+    /// Argon2id parameters above [`crate::Argon2idParams::check_bounds`].
+    KeyDerivationParamsOutOfRange {
+        memory_kib: u32,
+        iterations: u32,
+        parallelism: u32,
+    },
+    InvalidEnvelopeTooShort {
+        expected_at_least: usize,
+        found: usize,
+    },
+    UnsupportedEnvelopeVersion {
+        version: u8,
+    },
+    UnsupportedKeyDerivation {
+        id: u8,
+    },
+    UnsupportedCipher {
+        id: u8,
+    },
+    AssociatedDataMismatch,
 }
 
 impl CryptoError {
     pub fn aes_decryption_failed(_error: impl std::fmt::Debug) -> Self {
         Self::AesDecryptionFailed
     }
+
+    pub fn invalid_key_derivation_params(error: impl std::fmt::Display) -> Self {
+        Self::InvalidKeyDerivationParams {
+            reason: error.to_string(),
+        }
+    }
 }
 
 impl std::fmt::Display for CryptoError {
@@ -31,6 +60,37 @@ impl std::fmt::Display for CryptoError {
                 "Invalid AES bytes, expected at least {} bytes, but found {} bytes",
                 expected_at_least, found
             ),
+            Self::InvalidKeyDerivationParams { reason } => {
+                write!(f, "Invalid key derivation parameters: {}", reason)
+            }
+            Self::KeyDerivationParamsOutOfRange {
+                memory_kib,
+                iterations,
+                parallelism,
+            } => write!(
+                f,
+                "Key derivation parameters out of range: {} KiB of memory, {} iterations, {} lanes",
+                memory_kib, iterations, parallelism
+            ),
+            Self::InvalidEnvelopeTooShort {
+                expected_at_least,
+                found,
+            } => write!(
+                f,
+                "Invalid encrypted envelope, expected at least {} bytes, but found {} bytes",
+                expected_at_least, found
+            ),
+            Self::UnsupportedEnvelopeVersion { version } => {
+                write!(f, "Unsupported encrypted envelope version: {}", version)
+            }
+            Self::UnsupportedKeyDerivation { id } => {
+                write!(f, "Unsupported key derivation function id: {}", id)
+            }
+            Self::UnsupportedCipher { id } => write!(f, "Unsupported cipher id: {}", id),
+            Self::AssociatedDataMismatch => write!(
+                f,
+                "Encrypted data is bound to other associated data, e.g. another sender or SMTP server"
+            ),
         }
     }
 }
@@ -67,4 +127,22 @@ mod tests {
             "Invalid AES bytes, expected at least 32 bytes, but found 7 bytes"
         );
     }
+
+    #[test]
+    fn display_for_invalid_key_derivation_params() {
+        let error = CryptoError::invalid_key_derivation_params("time cost is too small");
+        assert_eq!(
+            error.to_string(),
+            "Invalid key derivation parameters: time cost is too small"
+        );
+    }
+
+    #[test]
+    fn display_for_unsupported_envelope_version() {
+        let error = CryptoError::UnsupportedEnvelopeVersion { version: 9 };
+        assert_eq!(
+            error.to_string(),
+            "Unsupported encrypted envelope version: 9"
+        );
+    }
 }
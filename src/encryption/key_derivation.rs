@@ -0,0 +1,31 @@
+use secrecy::SecretString;
+use serde::{Deserialize, Serialize};
+
+use super::{Argon2id, Argon2idParams, EncryptionKey, PbHkdfSha256, Result, Salt};
+
+/// The function used to derive an [`EncryptionKey`] from an encryption password and [`Salt`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "algorithm", rename_all = "snake_case")]
+pub enum KeyDerivation {
+    /// Single round HKDF-SHA256, offers no brute-force resistance, only
+    /// kept to decrypt app passwords encrypted by earlier versions.
+    HkdfSha256,
+
+    /// Memory-hard Argon2id with the given cost parameters.
+    Argon2id(Argon2idParams),
+}
+
+impl Default for KeyDerivation {
+    fn default() -> Self {
+        Self::Argon2id(Argon2idParams::default())
+    }
+}
+
+impl KeyDerivation {
+    pub fn derive_key_from(&self, password: SecretString, salt: &Salt) -> Result<EncryptionKey> {
+        match self {
+            Self::HkdfSha256 => Ok(PbHkdfSha256::derive_key_from(password, salt)),
+            Self::Argon2id(params) => Argon2id::derive_key_from(password, salt, params),
+        }
+    }
+}
@@ -1,15 +1,21 @@
 mod aes_gcm_256;
 mod aes_gcm_sealed_box;
+mod argon2id;
 mod encrypted_app_password;
 mod encryption_key;
+mod envelope;
 mod error;
+mod key_derivation;
 mod pb_hkdf;
 mod salt;
 
 pub use aes_gcm_256::AesGcm256;
 pub use aes_gcm_sealed_box::{AesGcmSealedBox, AesNonce};
+pub use argon2id::{Argon2id, Argon2idParams};
 pub use encrypted_app_password::EncryptedAppPassword;
 pub use encryption_key::EncryptionKey;
+pub use envelope::{Cipher, Envelope, EnvelopeVersion};
 pub use error::{CryptoError, Result};
+pub use key_derivation::KeyDerivation;
 pub use pb_hkdf::PbHkdfSha256;
 pub use salt::Salt;
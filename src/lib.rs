@@ -1,10 +1,19 @@
 mod email;
 mod encryption;
+#[cfg(feature = "merge")]
+mod merge;
+#[cfg(feature = "store")]
+mod store;
 #[cfg(feature = "tui")]
 pub mod tui;
 
 pub use email::*;
 pub use encryption::{
-    AesGcm256, AesGcmSealedBox, AesNonce, CryptoError, EncryptedAppPassword, EncryptionKey,
-    PbHkdfSha256, Result as CryptoResult, Salt,
+    AesGcm256, AesGcmSealedBox, AesNonce, Argon2id, Argon2idParams, Cipher, CryptoError,
+    EncryptedAppPassword, EncryptionKey, Envelope, EnvelopeVersion, KeyDerivation, PbHkdfSha256,
+    Result as CryptoResult, Salt,
 };
+#[cfg(feature = "merge")]
+pub use merge::*;
+#[cfg(feature = "store")]
+pub use store::*;
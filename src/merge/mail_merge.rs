@@ -0,0 +1,172 @@
+use bon::Builder;
+use getset::Getters;
+use indexmap::IndexSet;
+
+use crate::{
+    DecryptedEmailSettings, Email, EmailAccount, MailMergeError, MailMergeFailure, MailMergeReport,
+    MergeRecord, Template, TemplateBuiltins,
+};
+
+/// Renders a [`Template`] once per [`MergeRecord`], yielding one personalised
+/// [`Email`] to the recipient of each record.
+///
+/// Variables of a record that the template does not use are ignored, so a
+/// record source can carry extra columns. A failing record, e.g. one with an
+/// invalid email address or without a variable the template uses, does not
+/// stop the merge, see [`MailMerge::merge_all`].
+#[derive(Debug, Clone, PartialEq, Builder, Getters)]
+pub struct MailMerge {
+    #[getset(get = "pub")]
+    template: Template,
+
+    #[getset(get = "pub")]
+    sender: EmailAccount,
+
+    #[getset(get = "pub")]
+    reply_to: Option<EmailAccount>,
+
+    /// The built-in variables, the sender and recipient are set per record
+    /// and a seed, if any, is offset by the record number so that every
+    /// email gets its own `<RANDOM_ID>` and `<MESSAGE_ID>`.
+    #[builder(default)]
+    #[getset(get = "pub")]
+    builtins: TemplateBuiltins,
+}
+
+impl MailMerge {
+    /// Renders the email of every record, in order, failures carry the
+    /// 1-based number of the record.
+    pub fn merge<'a>(
+        &'a self,
+        records: impl IntoIterator<Item = Result<MergeRecord, MailMergeError>> + 'a,
+    ) -> impl Iterator<Item = Result<Email, MailMergeFailure>> + 'a {
+        records.into_iter().enumerate().map(|(index, record)| {
+            let number = index + 1;
+            record
+                .and_then(|record| self.render(number, &record))
+                .map_err(|error| MailMergeFailure::new(number, error))
+        })
+    }
+
+    /// Renders the email of every record and collects them into a report.
+    pub fn merge_all(
+        &self,
+        records: impl IntoIterator<Item = Result<MergeRecord, MailMergeError>>,
+    ) -> MailMergeReport {
+        self.merge(records).collect()
+    }
+
+    fn render(&self, number: usize, record: &MergeRecord) -> Result<Email, MailMergeError> {
+        let builtins = self
+            .builtins
+            .clone()
+            .with_seed(
+                self.builtins
+                    .seed()
+                    .map(|seed| seed.wrapping_add(number as u64)),
+            )
+            .with_sender(Some(self.sender.clone()))
+            .with_recipient(Some(record.recipient().clone()))
+            .variables();
+        let variables = record
+            .variables()
+            .restricted_to(&self.template.variables()?);
+        let (subject, body) = self.template.render_with_builtins(&variables, &builtins)?;
+        let (text_body, html_body) = body.into_parts();
+        Ok(Email::builder()
+            .public_recipients(IndexSet::from([record.recipient().clone()]))
+            .subject(subject)
+            .maybe_body(text_body)
+            .maybe_html_body(html_body)
+            .maybe_reply_to(self.reply_to.clone())
+            .maybe_message_id(
+                variables
+                    .get(TemplateBuiltins::MESSAGE_ID)
+                    .or_else(|| builtins.get(TemplateBuiltins::MESSAGE_ID))
+                    .map(ToString::to_string),
+            )
+            .build())
+    }
+}
+
+impl DecryptedEmailSettings {
+    /// A mail merge with the template, sender and reply-to of these settings,
+    /// the emails are sent with the credentials of these settings.
+    pub fn mail_merge(&self) -> MailMerge {
+        MailMerge::builder()
+            .template(self.template().clone())
+            .sender(self.sender().clone())
+            .maybe_reply_to(self.reply_to().clone())
+            .build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EmailBody, Recipient};
+
+    fn mail_merge() -> MailMerge {
+        MailMerge::builder()
+            .template(
+                Template::builder()
+                    .subject_format("Invoice <INV_NO>".into())
+                    .body_format(
+                        "Hi <RECIPIENT_NAME | default:\"there\">, please pay <AMOUNT>".into(),
+                    )
+                    .build(),
+            )
+            .sender(EmailAccount::sample_alice())
+            .builtins(TemplateBuiltins::builder().seed(0).build())
+            .build()
+    }
+
+    #[test]
+    fn merge_all_csv() {
+        let csv = "email,name,INV_NO,AMOUNT\n\
+                   bob@example.com,Bob Johnson,42,100 EUR\n\
+                   carol@example.com,,43,\n\
+                   dave@example.com,,44,200 EUR\n";
+        let report = mail_merge().merge_all(MergeRecord::read_csv(csv.as_bytes()));
+        assert!(!report.is_success());
+
+        let emails = report.emails();
+        assert_eq!(emails.len(), 2);
+        assert_eq!(
+            emails[0].public_recipients(),
+            &IndexSet::from([Recipient::sample_bob()])
+        );
+        assert_eq!(emails[0].subject(), "Invoice 42");
+        assert_eq!(
+            emails[0].email_body(),
+            EmailBody::from_text("Hi Bob Johnson, please pay 100 EUR")
+        );
+        assert_eq!(emails[1].body(), "Hi there, please pay 200 EUR");
+        assert_ne!(emails[0].message_id(), emails[1].message_id());
+
+        let failures = report.failures();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].record(), &2);
+        assert!(matches!(
+            failures[0].error(),
+            MailMergeError::Template(crate::TemplateError::MissingVariables { names }) if names == &["AMOUNT"]
+        ));
+    }
+
+    #[test]
+    fn merge_csv_ignores_unused_columns() {
+        let csv = "email,INV_NO,AMOUNT,NOTE\n\
+                   bob@example.com,42,100 EUR,VIP customer\n";
+        let report = mail_merge().merge_all(MergeRecord::read_csv(csv.as_bytes()));
+        assert!(report.is_success());
+        assert_eq!(report.emails()[0].body(), "Hi there, please pay 100 EUR");
+    }
+
+    #[test]
+    fn merge_is_deterministic_with_seed() {
+        let json = r#"[{"email": "bob@example.com", "INV_NO": 42, "AMOUNT": 100}]"#;
+        let merge = || mail_merge().merge_all(MergeRecord::read_json(json.as_bytes()));
+        assert!(merge().is_success());
+        assert_eq!(merge(), merge());
+    }
+}
@@ -0,0 +1,48 @@
+use std::fmt::{Display, Formatter};
+
+use crate::TemplateError;
+
+/// Errors of a single record of a [`crate::MailMerge`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum MailMergeError {
+    /// The CSV data could not be read.
+    Csv { underlying: String },
+
+    /// The JSON data could not be read.
+    Json { underlying: String },
+
+    /// A JSON record is not an object.
+    NotAnObject,
+
+    /// The record has no value in the email column, see [`crate::MergeRecord::EMAIL_COLUMN`].
+    MissingEmail,
+
+    /// The value of the email column is not a valid email address.
+    InvalidEmail { value: String, underlying: String },
+
+    /// The template could not be rendered with the variables of the record.
+    Template(TemplateError),
+}
+
+impl From<TemplateError> for MailMergeError {
+    fn from(error: TemplateError) -> Self {
+        Self::Template(error)
+    }
+}
+
+impl Display for MailMergeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Csv { underlying } => write!(f, "invalid CSV: {underlying}"),
+            Self::Json { underlying } => write!(f, "invalid JSON: {underlying}"),
+            Self::NotAnObject => write!(f, "record is not a JSON object"),
+            Self::MissingEmail => write!(f, "record has no email address"),
+            Self::InvalidEmail { value, underlying } => {
+                write!(f, "invalid email address '{value}': {underlying}")
+            }
+            Self::Template(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for MailMergeError {}
@@ -0,0 +1,56 @@
+use getset::Getters;
+
+use crate::{Email, MailMergeError};
+
+/// A record of a mail merge that did not result in an email.
+#[derive(Debug, Clone, PartialEq, Getters)]
+pub struct MailMergeFailure {
+    /// The 1-based number of the record, i.e. the row of the CSV data
+    /// excluding the header, or the element of the JSON array.
+    #[getset(get = "pub")]
+    record: usize,
+
+    #[getset(get = "pub")]
+    error: MailMergeError,
+}
+
+impl MailMergeFailure {
+    pub fn new(record: usize, error: MailMergeError) -> Self {
+        Self { record, error }
+    }
+}
+
+/// The outcome of [`crate::MailMerge::merge_all`], the personalised emails
+/// and the records that failed.
+#[derive(Debug, Clone, Default, PartialEq, Getters)]
+pub struct MailMergeReport {
+    #[getset(get = "pub")]
+    emails: Vec<Email>,
+
+    #[getset(get = "pub")]
+    failures: Vec<MailMergeFailure>,
+}
+
+impl MailMergeReport {
+    /// Whether every record resulted in an email.
+    pub fn is_success(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    pub fn into_parts(self) -> (Vec<Email>, Vec<MailMergeFailure>) {
+        (self.emails, self.failures)
+    }
+}
+
+impl FromIterator<Result<Email, MailMergeFailure>> for MailMergeReport {
+    fn from_iter<I: IntoIterator<Item = Result<Email, MailMergeFailure>>>(iter: I) -> Self {
+        let mut report = Self::default();
+        for outcome in iter {
+            match outcome {
+                Ok(email) => report.emails.push(email),
+                Err(failure) => report.failures.push(failure),
+            }
+        }
+        report
+    }
+}
@@ -0,0 +1,212 @@
+use std::io::Read;
+
+use bon::Builder;
+use getset::Getters;
+
+use crate::{EmailAddress, MailMergeError, Recipient, TemplateValue, TemplateVariables};
+
+/// A recipient of a [`crate::MailMerge`] and the template variables of their email.
+///
+/// Records are typically read from CSV, with a header row naming the columns,
+/// or from a JSON array of objects. The [`MergeRecord::EMAIL_COLUMN`] and the
+/// optional [`MergeRecord::NAME_COLUMN`], matched case-insensitively, make up
+/// the recipient and every other column is a variable, e.g.:
+///
+/// ```csv
+/// email,name,INV_NO,AMOUNT
+/// ap@acme.com,Acme Accounts,42,100
+/// ```
+///
+/// The name is available in templates as `<RECIPIENT_NAME>`, see
+/// [`crate::TemplateBuiltins`]. Columns the template does not use are ignored
+/// by the merge.
+#[derive(Debug, Clone, PartialEq, Builder, Getters)]
+pub struct MergeRecord {
+    #[getset(get = "pub")]
+    recipient: Recipient,
+
+    #[builder(default)]
+    #[getset(get = "pub")]
+    variables: TemplateVariables,
+}
+
+impl MergeRecord {
+    /// Name of the column holding the email address of the recipient.
+    pub const EMAIL_COLUMN: &str = "EMAIL";
+
+    /// Name of the optional column holding the name of the recipient.
+    pub const NAME_COLUMN: &str = "NAME";
+
+    /// Creates a record from named values, see [`MergeRecord`] for the columns.
+    pub fn from_values(
+        values: impl IntoIterator<Item = (String, TemplateValue)>,
+    ) -> Result<Self, MailMergeError> {
+        let mut email = None;
+        let mut name = None;
+        let mut variables = TemplateVariables::new();
+        for (column, value) in values {
+            if column.eq_ignore_ascii_case(Self::EMAIL_COLUMN) {
+                email = Some(value.to_string());
+            } else if column.eq_ignore_ascii_case(Self::NAME_COLUMN) {
+                name = Some(value.to_string()).filter(|name| !name.trim().is_empty());
+            } else {
+                variables.insert(column, value);
+            }
+        }
+
+        let email = email
+            .map(|email| email.trim().to_owned())
+            .filter(|email| !email.is_empty())
+            .ok_or(MailMergeError::MissingEmail)?;
+        let email =
+            email
+                .parse::<EmailAddress>()
+                .map_err(|error| MailMergeError::InvalidEmail {
+                    underlying: error.to_string(),
+                    value: email,
+                })?;
+        Ok(Self {
+            recipient: Recipient::builder().maybe_name(name).email(email).build(),
+            variables,
+        })
+    }
+
+    /// Reads records from CSV with a header row, one record per row.
+    ///
+    /// All values are text and empty cells are left out, so they can be
+    /// given a fallback with the `default` filter.
+    pub fn read_csv(reader: impl Read) -> impl Iterator<Item = Result<Self, MailMergeError>> {
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(reader);
+        let (headers, error) = match reader.headers() {
+            Ok(headers) => (headers.clone(), None),
+            Err(error) => (csv::StringRecord::new(), Some(csv_error(error))),
+        };
+        let records = error
+            .is_none()
+            .then(|| reader.into_records())
+            .into_iter()
+            .flatten();
+
+        error.map(Err).into_iter().chain(records.map(move |row| {
+            let row = row.map_err(csv_error)?;
+            Self::from_values(
+                headers
+                    .iter()
+                    .zip(row.iter())
+                    .filter(|(_, value)| !value.is_empty())
+                    .map(|(column, value)| (column.to_owned(), TemplateValue::from(value))),
+            )
+        }))
+    }
+
+    /// Reads records from a JSON array of objects, one record per object.
+    ///
+    /// Values keep their type, e.g. lists can be used with `<#each>`, and
+    /// `null` values are left out.
+    pub fn read_json(reader: impl Read) -> impl Iterator<Item = Result<Self, MailMergeError>> {
+        let (objects, error) = match serde_json::from_reader::<_, Vec<serde_json::Value>>(reader) {
+            Ok(objects) => (objects, None),
+            Err(error) => (Vec::new(), Some(json_error(error))),
+        };
+        error
+            .map(Err)
+            .into_iter()
+            .chain(objects.into_iter().map(Self::from_json))
+    }
+
+    fn from_json(object: serde_json::Value) -> Result<Self, MailMergeError> {
+        let serde_json::Value::Object(object) = object else {
+            return Err(MailMergeError::NotAnObject);
+        };
+        let values = object
+            .into_iter()
+            .filter(|(_, value)| !value.is_null())
+            .map(|(column, value)| {
+                serde_json::from_value::<TemplateValue>(value)
+                    .map(|value| (column, value))
+                    .map_err(json_error)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::from_values(values)
+    }
+}
+
+fn csv_error(error: csv::Error) -> MailMergeError {
+    MailMergeError::Csv {
+        underlying: error.to_string(),
+    }
+}
+
+fn json_error(error: serde_json::Error) -> MailMergeError {
+    MailMergeError::Json {
+        underlying: error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_csv() {
+        let csv =
+            "Email,Name,INV_NO,NOTE\nap@acme.com,Acme Accounts,42,\nbob@example.com,,43,Late\n";
+        let records = MergeRecord::read_csv(csv.as_bytes())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            records[0].recipient().to_string(),
+            "Acme Accounts <ap@acme.com>"
+        );
+        assert_eq!(
+            records[0].variables(),
+            &TemplateVariables::from([("INV_NO", "42")])
+        );
+        assert_eq!(
+            records[1].recipient(),
+            &Recipient::from(EmailAddress::sample_bob())
+        );
+        assert_eq!(
+            records[1].variables(),
+            &TemplateVariables::from([("INV_NO", "43"), ("NOTE", "Late")])
+        );
+    }
+
+    #[test]
+    fn read_csv_invalid_rows() {
+        let csv = "email,INV_NO\nnot an email,42\n,43\nap@acme.com,44,extra\n";
+        let records = MergeRecord::read_csv(csv.as_bytes()).collect::<Vec<_>>();
+        assert!(matches!(
+            records[0],
+            Err(MailMergeError::InvalidEmail { ref value, .. }) if value == "not an email"
+        ));
+        assert_eq!(records[1], Err(MailMergeError::MissingEmail));
+        assert!(matches!(records[2], Err(MailMergeError::Csv { .. })));
+    }
+
+    #[test]
+    fn read_json() {
+        let json = r#"[
+            {"email": "ap@acme.com", "INV_NO": 42, "ITEMS": ["Support", "Hosting"], "NOTE": null},
+            "ap@acme.com"
+        ]"#;
+        let records = MergeRecord::read_json(json.as_bytes()).collect::<Vec<_>>();
+        let record = records[0].as_ref().unwrap();
+        assert_eq!(record.recipient().name(), &None);
+        assert_eq!(
+            record.variables(),
+            &TemplateVariables::new()
+                .with("INV_NO", 42)
+                .with("ITEMS", vec!["Support", "Hosting"])
+        );
+        assert_eq!(records[1], Err(MailMergeError::NotAnObject));
+    }
+
+    #[test]
+    fn read_invalid_json() {
+        let records = MergeRecord::read_json("{".as_bytes()).collect::<Vec<_>>();
+        assert!(matches!(records[..], [Err(MailMergeError::Json { .. })]));
+    }
+}
@@ -0,0 +1,9 @@
+mod mail_merge;
+mod mail_merge_error;
+mod mail_merge_report;
+mod merge_record;
+
+pub use mail_merge::*;
+pub use mail_merge_error::*;
+pub use mail_merge_report::*;
+pub use merge_record::*;
@@ -0,0 +1,9 @@
+mod settings_format;
+mod settings_migration;
+mod settings_store;
+mod settings_store_error;
+
+pub use settings_format::*;
+pub use settings_migration::*;
+pub use settings_store::*;
+pub use settings_store_error::*;
@@ -0,0 +1,53 @@
+use std::path::Path;
+
+use serde::{Serialize, de::DeserializeOwned};
+
+/// File format of a persisted settings file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, derive_more::Display)]
+pub enum SettingsFormat {
+    #[default]
+    #[display("TOML")]
+    Toml,
+
+    #[display("JSON")]
+    Json,
+}
+
+impl SettingsFormat {
+    /// The format implied by the extension of `path`, `.json` means JSON and
+    /// anything else TOML.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("json") => Self::Json,
+            _ => Self::Toml,
+        }
+    }
+
+    /// The conventional file extension of this format, without leading dot.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Toml => "toml",
+            Self::Json => "json",
+        }
+    }
+
+    pub(crate) fn serialize<T: Serialize>(
+        &self,
+        value: &T,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        match self {
+            Self::Toml => toml::to_string_pretty(value).map_err(Into::into),
+            Self::Json => serde_json::to_string_pretty(value).map_err(Into::into),
+        }
+    }
+
+    pub(crate) fn deserialize<T: DeserializeOwned>(
+        &self,
+        contents: &str,
+    ) -> Result<T, Box<dyn std::error::Error + Send + Sync>> {
+        match self {
+            Self::Toml => toml::from_str(contents).map_err(Into::into),
+            Self::Json => serde_json::from_str(contents).map_err(Into::into),
+        }
+    }
+}
@@ -0,0 +1,298 @@
+use serde_json::{Map, Value};
+
+use crate::EmailProfiles;
+
+/// Version of the serialized settings format written by this version of mejla,
+/// stored under [`SETTINGS_SCHEMA_VERSION_KEY`].
+///
+/// Documents without a version are version `0`, the format of mejla `0.1.4`.
+pub const CURRENT_SETTINGS_SCHEMA_VERSION: u32 = 2;
+
+/// Key of the schema version in serialized settings documents.
+pub const SETTINGS_SCHEMA_VERSION_KEY: &str = "schema_version";
+
+/// Upgrades a document from version `n` to `n + 1`, where `n` is its index.
+type Migration = fn(&mut Map<String, Value>) -> Result<(), String>;
+
+const MIGRATIONS: [Migration; CURRENT_SETTINGS_SCHEMA_VERSION as usize] =
+    [migrate_v0_to_v1, migrate_v1_to_v2];
+
+/// Errors that can occur while migrating a settings document to the current schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SettingsMigrationError {
+    /// The document is not a map/table.
+    NotAMap,
+
+    /// The schema version is not a non-negative integer.
+    InvalidSchemaVersion { found: String },
+
+    /// The document was written by a newer version of mejla.
+    UnsupportedSchemaVersion { version: u32 },
+
+    /// Migrating from version `from` to the next version failed.
+    MigrationFailed { from: u32, underlying: String },
+}
+
+impl std::fmt::Display for SettingsMigrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotAMap => write!(f, "settings document is not a map"),
+            Self::InvalidSchemaVersion { found } => {
+                write!(f, "invalid settings schema version: {found}")
+            }
+            Self::UnsupportedSchemaVersion { version } => write!(
+                f,
+                "settings schema version {version} is newer than the latest supported version {CURRENT_SETTINGS_SCHEMA_VERSION}"
+            ),
+            Self::MigrationFailed { from, underlying } => write!(
+                f,
+                "failed to migrate settings from schema version {from} to {}: {underlying}",
+                from + 1
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SettingsMigrationError {}
+
+/// The schema version of `document`, `0` if it has none.
+pub fn settings_schema_version(document: &Value) -> Result<u32, SettingsMigrationError> {
+    let document = document
+        .as_object()
+        .ok_or(SettingsMigrationError::NotAMap)?;
+    match document.get(SETTINGS_SCHEMA_VERSION_KEY) {
+        None => Ok(0),
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| SettingsMigrationError::InvalidSchemaVersion {
+                found: version.to_string(),
+            }),
+    }
+}
+
+/// Upgrades `document` step by step to [`CURRENT_SETTINGS_SCHEMA_VERSION`].
+pub fn migrate_settings_document(mut document: Value) -> Result<Value, SettingsMigrationError> {
+    let version = settings_schema_version(&document)?;
+    if version > CURRENT_SETTINGS_SCHEMA_VERSION {
+        return Err(SettingsMigrationError::UnsupportedSchemaVersion { version });
+    }
+    let map = document
+        .as_object_mut()
+        .ok_or(SettingsMigrationError::NotAMap)?;
+    for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        let from = from as u32;
+        migration(map)
+            .map_err(|underlying| SettingsMigrationError::MigrationFailed { from, underlying })?;
+        map.insert(
+            SETTINGS_SCHEMA_VERSION_KEY.to_owned(),
+            Value::from(from + 1),
+        );
+    }
+    Ok(document)
+}
+
+/// Prepends the current schema version to the serialized settings `document`.
+pub(crate) fn stamp_settings_schema_version(document: Value) -> Value {
+    let Value::Object(fields) = document else {
+        return document;
+    };
+    let mut stamped = Map::with_capacity(fields.len() + 1);
+    stamped.insert(
+        SETTINGS_SCHEMA_VERSION_KEY.to_owned(),
+        Value::from(CURRENT_SETTINGS_SCHEMA_VERSION),
+    );
+    stamped.extend(fields);
+    Value::Object(stamped)
+}
+
+/// Version `1` made the SMTP server a table of host, port and TLS mode
+/// instead of a plain hostname, which meant implicit TLS on port 465.
+fn migrate_v0_to_v1(document: &mut Map<String, Value>) -> Result<(), String> {
+    if let Some(Value::String(host)) = document.get("smtp_server") {
+        let smtp_server = serde_json::json!({
+            "host": host,
+            "port": 465,
+            "tls": "implicit",
+        });
+        document.insert("smtp_server".to_owned(), smtp_server);
+    }
+    if document.get("reply_to").is_some_and(Value::is_null) {
+        document.remove("reply_to");
+    }
+    Ok(())
+}
+
+/// Version `2` introduced named profiles, the settings of version `1` become
+/// the only profile, named [`EmailProfiles::DEFAULT_PROFILE_NAME`].
+fn migrate_v1_to_v2(document: &mut Map<String, Value>) -> Result<(), String> {
+    let mut settings = std::mem::take(document);
+    settings.remove(SETTINGS_SCHEMA_VERSION_KEY);
+    let name = EmailProfiles::DEFAULT_PROFILE_NAME;
+    document.insert("default_profile".to_owned(), Value::from(name));
+    document.insert(
+        "profiles".to_owned(),
+        Value::Object(Map::from_iter([(name.to_owned(), Value::Object(settings))])),
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EncryptedEmailSettings, Recipient, SettingsFormat};
+    use indexmap::IndexSet;
+
+    /// The sample settings as written by mejla `0.1.4`, without recipient names.
+    fn sample_v0() -> EncryptedEmailSettings {
+        let sample = EncryptedEmailSettings::sample();
+        let bare = |recipient: &Recipient| Recipient::from(recipient.email().clone());
+        EncryptedEmailSettings::builder()
+            .smtp_app_password(sample.smtp_app_password().clone())
+            .salt(sample.salt().clone())
+            .template(sample.template().clone())
+            .smtp_server(sample.smtp_server().clone())
+            .sender(sample.sender().clone())
+            .recipients(
+                sample
+                    .recipients()
+                    .iter()
+                    .map(bare)
+                    .collect::<IndexSet<_>>(),
+            )
+            .cc_recipients(sample.cc_recipients().clone())
+            .bcc_recipients(sample.bcc_recipients().clone())
+            .build()
+    }
+
+    fn migrated(format: SettingsFormat, fixture: &str) -> EmailProfiles {
+        let document = format.deserialize::<Value>(fixture).unwrap();
+        let document = migrate_settings_document(document).unwrap();
+        assert_eq!(
+            settings_schema_version(&document).unwrap(),
+            CURRENT_SETTINGS_SCHEMA_VERSION
+        );
+        serde_json::from_value(document).unwrap()
+    }
+
+    #[test]
+    fn migrate_v0_json() {
+        assert_eq!(
+            migrated(
+                SettingsFormat::Json,
+                include_str!("../../fixtures/email_settings/v0.json")
+            ),
+            EmailProfiles::new(EmailProfiles::DEFAULT_PROFILE_NAME, sample_v0()).unwrap()
+        );
+    }
+
+    #[test]
+    fn migrate_v0_toml() {
+        assert_eq!(
+            migrated(
+                SettingsFormat::Toml,
+                include_str!("../../fixtures/email_settings/v0.toml")
+            ),
+            EmailProfiles::new(EmailProfiles::DEFAULT_PROFILE_NAME, sample_v0()).unwrap()
+        );
+    }
+
+    #[test]
+    fn migrate_v1_json() {
+        assert_eq!(
+            migrated(
+                SettingsFormat::Json,
+                include_str!("../../fixtures/email_settings/v1.json")
+            ),
+            EmailProfiles::new(
+                EmailProfiles::DEFAULT_PROFILE_NAME,
+                EncryptedEmailSettings::sample()
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn migrate_v1_toml() {
+        assert_eq!(
+            migrated(
+                SettingsFormat::Toml,
+                include_str!("../../fixtures/email_settings/v1.toml")
+            ),
+            EmailProfiles::new(
+                EmailProfiles::DEFAULT_PROFILE_NAME,
+                EncryptedEmailSettings::sample()
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn migrate_v2_json() {
+        assert_eq!(
+            migrated(
+                SettingsFormat::Json,
+                include_str!("../../fixtures/email_settings/v2.json")
+            ),
+            EmailProfiles::sample()
+        );
+    }
+
+    #[test]
+    fn migrate_v2_toml() {
+        assert_eq!(
+            migrated(
+                SettingsFormat::Toml,
+                include_str!("../../fixtures/email_settings/v2.toml")
+            ),
+            EmailProfiles::sample()
+        );
+    }
+
+    #[test]
+    fn migrate_v0_smtp_server() {
+        let mut document = Map::from_iter([("smtp_server".to_owned(), "smtp.example.com".into())]);
+        migrate_v0_to_v1(&mut document).unwrap();
+        assert_eq!(
+            Value::Object(document),
+            serde_json::json!({
+                "smtp_server": { "host": "smtp.example.com", "port": 465, "tls": "implicit" },
+            })
+        );
+    }
+
+    #[test]
+    fn migrate_v1_into_default_profile() {
+        let document = serde_json::json!({ "schema_version": 1, "salt": "ab" });
+        assert_eq!(
+            migrate_settings_document(document).unwrap(),
+            serde_json::json!({
+                "default_profile": "default",
+                "profiles": { "default": { "salt": "ab" } },
+                "schema_version": 2,
+            })
+        );
+    }
+
+    #[test]
+    fn newer_schema_version_is_unsupported() {
+        let document = serde_json::json!({ "schema_version": CURRENT_SETTINGS_SCHEMA_VERSION + 1 });
+        assert_eq!(
+            migrate_settings_document(document),
+            Err(SettingsMigrationError::UnsupportedSchemaVersion {
+                version: CURRENT_SETTINGS_SCHEMA_VERSION + 1
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_schema_version() {
+        let document = serde_json::json!({ "schema_version": "one" });
+        assert_eq!(
+            migrate_settings_document(document),
+            Err(SettingsMigrationError::InvalidSchemaVersion {
+                found: r#""one""#.to_owned()
+            })
+        );
+    }
+}
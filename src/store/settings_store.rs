@@ -0,0 +1,287 @@
+use std::{
+    io::Write as _,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    EmailProfiles, SettingsFormat, SettingsStoreError, migrate_settings_document,
+    stamp_settings_schema_version,
+};
+
+/// Loads and saves [`EmailProfiles`] from a TOML or JSON file.
+///
+/// Saved files contain the [`crate::CURRENT_SETTINGS_SCHEMA_VERSION`], and
+/// files written by older versions of mejla are migrated when loaded.
+///
+/// Writes are atomic, the settings are written to a temporary file in the
+/// same directory which then replaces the settings file, and on Unix the
+/// file is only readable and writable by its owner (`0600`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettingsStore {
+    path: PathBuf,
+    format: SettingsFormat,
+}
+
+impl SettingsStore {
+    /// Name of the directory within the config directory holding mejla's files.
+    pub const APP_DIRECTORY_NAME: &str = "mejla";
+
+    /// Name of the settings file, without extension.
+    pub const FILE_STEM: &str = "email_settings";
+
+    /// Creates a store for the file at `path`, with the format implied by its
+    /// extension, see [`SettingsFormat::from_path`].
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let format = SettingsFormat::from_path(&path);
+        Self::with_format(path, format)
+    }
+
+    /// Creates a store for the file at `path` using `format` regardless of its extension.
+    pub fn with_format(path: impl Into<PathBuf>, format: SettingsFormat) -> Self {
+        Self {
+            path: path.into(),
+            format,
+        }
+    }
+
+    /// The directory mejla's settings are stored in by default.
+    ///
+    /// This is `$XDG_CONFIG_HOME/mejla` when that variable holds an absolute
+    /// path, on every platform. Otherwise it is `~/.config/mejla` on Unix,
+    /// including macOS, and `%APPDATA%\mejla` on Windows.
+    pub fn default_directory() -> Result<PathBuf, SettingsStoreError> {
+        config_directory(std::env::var_os("XDG_CONFIG_HOME"))
+            .map(|directory| directory.join(Self::APP_DIRECTORY_NAME))
+            .ok_or(SettingsStoreError::NoConfigDirectory)
+    }
+
+    /// A store for `email_settings.<extension>` in [`SettingsStore::default_directory`].
+    pub fn discover(format: SettingsFormat) -> Result<Self, SettingsStoreError> {
+        let file_name = format!("{}.{}", Self::FILE_STEM, format.extension());
+        Self::default_directory()
+            .map(|directory| Self::with_format(directory.join(file_name), format))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn format(&self) -> SettingsFormat {
+        self.format
+    }
+
+    /// Whether the settings file exists.
+    pub fn exists(&self) -> bool {
+        self.path.is_file()
+    }
+
+    /// Reads and parses the settings file, migrating it to the current schema if needed.
+    pub fn load(&self) -> Result<EmailProfiles, SettingsStoreError> {
+        let contents = std::fs::read_to_string(&self.path).map_err(|underlying| {
+            if underlying.kind() == std::io::ErrorKind::NotFound {
+                SettingsStoreError::NotFound {
+                    path: self.path.clone(),
+                }
+            } else {
+                SettingsStoreError::Read {
+                    path: self.path.clone(),
+                    underlying,
+                }
+            }
+        })?;
+        let corrupt = |underlying| SettingsStoreError::Corrupt {
+            path: self.path.clone(),
+            format: self.format,
+            underlying,
+        };
+        let document = self.format.deserialize(&contents).map_err(corrupt)?;
+        let document = migrate_settings_document(document).map_err(|underlying| {
+            SettingsStoreError::Migration {
+                path: self.path.clone(),
+                underlying,
+            }
+        })?;
+        serde_json::from_value(document).map_err(|e| corrupt(e.into()))
+    }
+
+    /// Like [`SettingsStore::load`] but returns `None` if there is no settings file.
+    pub fn load_if_exists(&self) -> Result<Option<EmailProfiles>, SettingsStoreError> {
+        match self.load() {
+            Ok(settings) => Ok(Some(settings)),
+            Err(SettingsStoreError::NotFound { .. }) => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Atomically writes `profiles` to the settings file, creating its
+    /// directory if needed.
+    pub fn save(&self, profiles: &EmailProfiles) -> Result<(), SettingsStoreError> {
+        let serialize_error = |underlying| SettingsStoreError::Serialize {
+            format: self.format,
+            underlying,
+        };
+        let document = serde_json::to_value(profiles).map_err(|e| serialize_error(e.into()))?;
+        let contents = self
+            .format
+            .serialize(&stamp_settings_schema_version(document))
+            .map_err(serialize_error)?;
+        self.write_atomically(contents.as_bytes())
+            .map_err(|underlying| SettingsStoreError::Write {
+                path: self.path.clone(),
+                underlying,
+            })
+    }
+
+    fn write_atomically(&self, contents: &[u8]) -> std::io::Result<()> {
+        let directory = match self.path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        std::fs::create_dir_all(directory)?;
+        // The temporary file is created with `0600` permissions on Unix,
+        // which are kept when it replaces the settings file.
+        let mut file = tempfile::NamedTempFile::new_in(directory)?;
+        file.write_all(contents)?;
+        file.as_file().sync_all()?;
+        file.persist(&self.path).map_err(|error| error.error)?;
+        Ok(())
+    }
+}
+
+/// The XDG config directory `xdg_config_home`, if absolute, falling back to
+/// the platform's conventional config directory.
+fn config_directory(xdg_config_home: Option<std::ffi::OsString>) -> Option<PathBuf> {
+    xdg_config_home
+        .map(PathBuf::from)
+        .filter(|directory| directory.is_absolute())
+        .or_else(platform_config_directory)
+}
+
+#[cfg(unix)]
+fn platform_config_directory() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".config"))
+}
+
+#[cfg(not(unix))]
+fn platform_config_directory() -> Option<PathBuf> {
+    dirs::config_dir()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EncryptedEmailSettings;
+
+    #[test]
+    fn config_directory_prefers_absolute_xdg_config_home() {
+        assert_eq!(
+            config_directory(Some("/tmp/xdg".into())),
+            Some(PathBuf::from("/tmp/xdg"))
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn config_directory_ignores_relative_xdg_config_home() {
+        assert_eq!(
+            config_directory(Some("relative".into())),
+            dirs::home_dir().map(|home| home.join(".config"))
+        );
+    }
+
+    #[test]
+    fn toml_roundtrip() {
+        let directory = tempfile::tempdir().unwrap();
+        let store = SettingsStore::new(directory.path().join("settings.toml"));
+        assert_eq!(store.format(), SettingsFormat::Toml);
+        store.save(&EmailProfiles::sample()).unwrap();
+        assert_eq!(store.load().unwrap(), EmailProfiles::sample());
+    }
+
+    #[test]
+    fn json_roundtrip_overwrites() {
+        let directory = tempfile::tempdir().unwrap();
+        let store = SettingsStore::new(directory.path().join("nested/settings.json"));
+        assert_eq!(store.format(), SettingsFormat::Json);
+        let noreply =
+            EmailProfiles::new("noreply", EncryptedEmailSettings::sample_other()).unwrap();
+        store.save(&EmailProfiles::sample()).unwrap();
+        store.save(&noreply).unwrap();
+        assert_eq!(store.load().unwrap(), noreply);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn saved_file_is_only_accessible_by_owner() {
+        use std::os::unix::fs::PermissionsExt as _;
+        let directory = tempfile::tempdir().unwrap();
+        let store = SettingsStore::new(directory.path().join("settings.toml"));
+        store.save(&EmailProfiles::sample()).unwrap();
+        let mode = std::fs::metadata(store.path())
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn saved_file_contains_schema_version() {
+        let directory = tempfile::tempdir().unwrap();
+        let store = SettingsStore::new(directory.path().join("settings.toml"));
+        store.save(&EmailProfiles::sample()).unwrap();
+        let contents = std::fs::read_to_string(store.path()).unwrap();
+        assert!(contents.starts_with(&format!(
+            "schema_version = {}",
+            crate::CURRENT_SETTINGS_SCHEMA_VERSION
+        )));
+    }
+
+    #[test]
+    fn load_migrates_legacy_file() {
+        let directory = tempfile::tempdir().unwrap();
+        let store = SettingsStore::new(directory.path().join("settings.json"));
+        std::fs::write(
+            store.path(),
+            include_str!("../../fixtures/email_settings/v0.json"),
+        )
+        .unwrap();
+        let profiles = store.load().unwrap();
+        assert_eq!(
+            profiles.default_profile_name(),
+            EmailProfiles::DEFAULT_PROFILE_NAME
+        );
+        assert_eq!(
+            profiles.default_profile().smtp_server(),
+            &crate::SmtpServer::gmail()
+        );
+    }
+
+    #[test]
+    fn load_missing_file() {
+        let directory = tempfile::tempdir().unwrap();
+        let store = SettingsStore::new(directory.path().join("settings.toml"));
+        assert!(matches!(
+            store.load(),
+            Err(SettingsStoreError::NotFound { .. })
+        ));
+        assert_eq!(store.load_if_exists().unwrap(), None);
+    }
+
+    #[test]
+    fn load_corrupt_file() {
+        let directory = tempfile::tempdir().unwrap();
+        let store = SettingsStore::new(directory.path().join("settings.json"));
+        std::fs::write(store.path(), "{ not json").unwrap();
+        let error = store.load().unwrap_err();
+        assert!(matches!(
+            error,
+            SettingsStoreError::Corrupt {
+                format: SettingsFormat::Json,
+                ..
+            }
+        ));
+        let source = std::error::Error::source(&error).unwrap();
+        assert!(source.downcast_ref::<serde_json::Error>().is_some());
+    }
+}
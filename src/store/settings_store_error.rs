@@ -0,0 +1,106 @@
+use std::{
+    fmt::{Display, Formatter},
+    path::PathBuf,
+};
+
+use crate::{SettingsFormat, SettingsMigrationError};
+
+/// Errors that can occur while loading or saving settings with a [`crate::SettingsStore`].
+#[derive(Debug)]
+pub enum SettingsStoreError {
+    /// The platform has no config directory, e.g. because `$HOME` is not set.
+    NoConfigDirectory,
+
+    /// There is no settings file at `path`.
+    NotFound { path: PathBuf },
+
+    /// The settings file at `path` exists but could not be read.
+    Read {
+        path: PathBuf,
+        underlying: std::io::Error,
+    },
+
+    /// The settings file at `path` is not valid settings in `format`.
+    Corrupt {
+        path: PathBuf,
+        format: SettingsFormat,
+        underlying: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    /// The settings file at `path` could not be migrated to the current schema.
+    Migration {
+        path: PathBuf,
+        underlying: SettingsMigrationError,
+    },
+
+    /// The settings could not be serialized into `format`.
+    Serialize {
+        format: SettingsFormat,
+        underlying: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    /// The settings could not be written to `path`.
+    Write {
+        path: PathBuf,
+        underlying: std::io::Error,
+    },
+}
+
+impl Display for SettingsStoreError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoConfigDirectory => write!(f, "could not determine the config directory"),
+            Self::NotFound { path } => {
+                write!(f, "no settings file found at {}", path.display())
+            }
+            Self::Read { path, underlying } => {
+                write!(
+                    f,
+                    "failed to read settings file {}: {underlying}",
+                    path.display()
+                )
+            }
+            Self::Corrupt {
+                path,
+                format,
+                underlying,
+            } => {
+                write!(
+                    f,
+                    "settings file {} is not valid {format}: {underlying}",
+                    path.display()
+                )
+            }
+            Self::Migration { path, underlying } => {
+                write!(
+                    f,
+                    "failed to migrate settings file {}: {underlying}",
+                    path.display()
+                )
+            }
+            Self::Serialize { format, underlying } => {
+                write!(f, "failed to serialize settings as {format}: {underlying}")
+            }
+            Self::Write { path, underlying } => {
+                write!(
+                    f,
+                    "failed to write settings file {}: {underlying}",
+                    path.display()
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for SettingsStoreError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Read { underlying, .. } | Self::Write { underlying, .. } => Some(underlying),
+            Self::Migration { underlying, .. } => Some(underlying),
+            Self::Corrupt { underlying, .. } | Self::Serialize { underlying, .. } => {
+                Some(underlying.as_ref())
+            }
+            Self::NoConfigDirectory | Self::NotFound { .. } => None,
+        }
+    }
+}
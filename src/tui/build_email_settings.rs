@@ -1,10 +1,12 @@
 use crate::{
-    EmailAccount, EmailAddress, EmailSettingsSelector, EncryptedAppPassword,
-    EncryptedEmailSettings, SmtpServer, Template,
+    CryptoError, EmailAccount, EmailProfiles, EmailProfilesError, EmailSettingsSelector,
+    EncryptedAppPassword, EncryptedEmailSettings, Recipient, SmtpAutoconfig, SmtpServer, Template,
 };
 use indexmap::IndexSet;
 use secrecy::SecretString;
 
+use super::smtp_server::preset_smtp_server;
+
 fn select_or_default<T, E, F>(
     selector: Option<EmailSettingsSelector>,
     target: EmailSettingsSelector,
@@ -26,6 +28,12 @@ where
     }
 }
 
+/// Asks for the settings picked by `data_selector`, or all of them if `None`,
+/// keeping the others from `default`.
+///
+/// `decrypt_error` maps the failure to decrypt the stored app password with
+/// the current encryption password, which is asked for when the encryption
+/// password, sender or SMTP server changes.
 #[allow(clippy::too_many_arguments)]
 pub fn ask_for_email<E>(
     default: EncryptedEmailSettings,
@@ -33,48 +41,62 @@ pub fn ask_for_email<E>(
     config_render: impl FnOnce(),
     ask_for_password: impl FnOnce(bool, &str, &str) -> Result<SecretString, E>,
     ask_for_email_encryption_password_with_confirmation: impl FnOnce(bool) -> Result<SecretString, E>,
+    ask_for_current_email_encryption_password: impl FnOnce() -> Result<SecretString, E>,
     ask_for_smtp_server: impl FnOnce(&SmtpServer) -> Result<SmtpServer, E>,
     ask_for_sender: impl FnOnce(&EmailAccount) -> Result<EmailAccount, E>,
     ask_for_template: impl FnOnce(&Template) -> Result<Template, E>,
     ask_for_reply_to: impl FnOnce(Option<&EmailAccount>) -> Result<Option<EmailAccount>, E>,
-    ask_for_recipients: impl FnOnce(&IndexSet<EmailAddress>) -> Result<IndexSet<EmailAddress>, E>,
-    ask_for_cc_recipients: impl FnOnce(&IndexSet<EmailAddress>) -> Result<IndexSet<EmailAddress>, E>,
-    ask_for_bcc_recipients: impl FnOnce(&IndexSet<EmailAddress>) -> Result<IndexSet<EmailAddress>, E>,
+    ask_for_recipients: impl FnOnce(&IndexSet<Recipient>) -> Result<IndexSet<Recipient>, E>,
+    ask_for_cc_recipients: impl FnOnce(&IndexSet<Recipient>) -> Result<IndexSet<Recipient>, E>,
+    ask_for_bcc_recipients: impl FnOnce(&IndexSet<Recipient>) -> Result<IndexSet<Recipient>, E>,
     recipients_empty_error: impl FnOnce() -> E,
+    decrypt_error: impl FnOnce(CryptoError) -> E,
     on_built: impl FnOnce(&EncryptedEmailSettings),
 ) -> Result<EncryptedEmailSettings, E> {
     config_render();
 
+    if data_selector == Some(EmailSettingsSelector::EncryptionPassword) {
+        // Only the encryption password changes, so the app password is
+        // re-encrypted without having to be re-entered.
+        let old_encryption_password = ask_for_current_email_encryption_password()?;
+        let new_encryption_password = ask_for_email_encryption_password_with_confirmation(true)?;
+        let email_settings = default
+            .rekey(old_encryption_password, new_encryption_password)
+            .map_err(decrypt_error)?;
+        on_built(&email_settings);
+        return Ok(email_settings);
+    }
+
     let is_editing_but_skip_secrets = data_selector
         .as_ref()
         .map(|s| !s.requires_encryption_password())
         .unwrap_or(false);
 
-    let (salt, app_password_encrypted) = if is_editing_but_skip_secrets {
-        (default.salt().clone(), default.smtp_app_password().clone())
+    // Secrets are asked for first, but encrypted only once the sender and SMTP
+    // server are known, since the encrypted app password is bound to both.
+    let secrets = if is_editing_but_skip_secrets {
+        None
+    } else if matches!(
+        data_selector,
+        Some(EmailSettingsSelector::SmtpServer | EmailSettingsSelector::Sender)
+    ) {
+        // The app password and encryption password stay the same, the app
+        // password is only re-encrypted for the new sender or SMTP server.
+        let encryption_password = ask_for_current_email_encryption_password()?;
+        let decrypted = default
+            .decrypt_smtp_app_password(encryption_password.clone())
+            .map_err(decrypt_error)?;
+        Some((decrypted.smtp_app_password().clone(), encryption_password))
     } else {
         let app_password_plaintext = ask_for_password(
             true,
             "SMTP App Password",
             "Used to authenticate sender account",
         )?;
-        let salt = crate::Salt::generate();
         let encryption_password = ask_for_email_encryption_password_with_confirmation(true)?;
-        let encryption_key = EncryptedAppPassword::new_by_deriving_and_encrypting(
-            app_password_plaintext,
-            encryption_password,
-            &salt,
-        );
-        (salt, encryption_key)
+        Some((app_password_plaintext, encryption_password))
     };
 
-    let smtp_server = select_or_default(
-        data_selector,
-        EmailSettingsSelector::SmtpServer,
-        default.smtp_server(),
-        ask_for_smtp_server,
-    )?;
-
     let sender = select_or_default(
         data_selector,
         EmailSettingsSelector::Sender,
@@ -82,6 +104,38 @@ pub fn ask_for_email<E>(
         ask_for_sender,
     )?;
 
+    // When the sender changes and the SMTP server is asked for, pre-fill the
+    // SMTP server of its provider, if known. Otherwise the server is kept.
+    let asks_for_smtp_server = data_selector
+        .map(|s| s.includes(EmailSettingsSelector::SmtpServer))
+        .unwrap_or(true);
+    let default_smtp_server = match SmtpAutoconfig::preset_for(sender.email()) {
+        Some(preset) if asks_for_smtp_server && sender.email() != default.sender().email() => {
+            preset_smtp_server(preset, default.smtp_server())
+        }
+        _ => default.smtp_server().clone(),
+    };
+    let smtp_server = select_or_default(
+        data_selector,
+        EmailSettingsSelector::SmtpServer,
+        &default_smtp_server,
+        ask_for_smtp_server,
+    )?;
+
+    let (salt, app_password_encrypted) = match secrets {
+        None => (default.salt().clone(), default.smtp_app_password().clone()),
+        Some((app_password_plaintext, encryption_password)) => {
+            let salt = crate::Salt::generate();
+            let encrypted = EncryptedAppPassword::new_by_deriving_and_encrypting(
+                app_password_plaintext,
+                encryption_password,
+                &salt,
+                &EncryptedEmailSettings::app_password_associated_data_for(&sender, &smtp_server),
+            );
+            (salt, encrypted)
+        }
+    };
+
     let template = select_or_default(
         data_selector,
         EmailSettingsSelector::Template,
@@ -137,3 +191,135 @@ pub fn ask_for_email<E>(
 
     Ok(email_settings)
 }
+
+/// Asks which profile of `profiles` to edit, then edits it with `ask_for_email`
+/// and stores the result under the picked name.
+///
+/// `ask_for_email` is called with the settings of the picked profile and
+/// `false`, or for a new profile with the settings of the default profile and
+/// `true`, typically meaning that all settings, including secrets, are asked for.
+pub fn ask_for_email_profile<E>(
+    mut profiles: EmailProfiles,
+    ask_for_profile_name: impl FnOnce(&EmailProfiles) -> Result<String, E>,
+    ask_for_email: impl FnOnce(EncryptedEmailSettings, bool) -> Result<EncryptedEmailSettings, E>,
+    on_profiles_error: impl FnOnce(EmailProfilesError) -> E,
+) -> Result<(String, EmailProfiles), E> {
+    let name = ask_for_profile_name(&profiles)?;
+    let email_settings = match profiles.get(&name) {
+        Some(existing) => ask_for_email(existing.clone(), false)?,
+        None => ask_for_email(profiles.default_profile().clone(), true)?,
+    };
+    profiles
+        .insert(name.clone(), email_settings)
+        .map_err(on_profiles_error)?;
+    Ok((name, profiles))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EmailAddress, KeyDerivation, Salt, TlsMode};
+    use secrecy::ExposeSecret;
+    use std::{str::FromStr, time::Duration};
+
+    fn custom_smtp_server() -> SmtpServer {
+        SmtpServer::builder()
+            .host("mail.example.com")
+            .port(2525)
+            .tls(TlsMode::StartTls)
+            .timeout(Duration::from_secs(30))
+            .max_message_size(5_000_000)
+            .build()
+    }
+
+    fn encrypted_settings() -> EncryptedEmailSettings {
+        let sender = EmailAccount::sample_alice();
+        let smtp_server = custom_smtp_server();
+        let salt = Salt::sample();
+        let smtp_app_password = EncryptedAppPassword::new_by_deriving_and_encrypting_with(
+            SecretString::from("app password"),
+            SecretString::from("open sesame"),
+            &salt,
+            KeyDerivation::HkdfSha256,
+            &EncryptedEmailSettings::app_password_associated_data_for(&sender, &smtp_server),
+        )
+        .unwrap();
+        EncryptedEmailSettings::builder()
+            .smtp_app_password(smtp_app_password)
+            .salt(salt)
+            .template(Template::default())
+            .sender(sender)
+            .smtp_server(smtp_server)
+            .recipients(IndexSet::from([Recipient::sample_bob()]))
+            .cc_recipients(IndexSet::new())
+            .bcc_recipients(IndexSet::new())
+            .build()
+    }
+
+    /// Edits `default` with `data_selector`, answering the sender prompt with
+    /// `sender` and failing on any secret prompt but the current encryption password.
+    fn edit(
+        default: EncryptedEmailSettings,
+        data_selector: EmailSettingsSelector,
+        sender: EmailAccount,
+    ) -> Result<EncryptedEmailSettings, String> {
+        ask_for_email(
+            default,
+            Some(data_selector),
+            || {},
+            |_, _, _| Err("asked for the app password".to_owned()),
+            |_| Err("asked for a new encryption password".to_owned()),
+            || Ok(SecretString::from("open sesame")),
+            |_| Err("asked for the SMTP server".to_owned()),
+            |_| Ok(sender),
+            |_| Err("asked for the template".to_owned()),
+            |_| Err("asked for reply-to".to_owned()),
+            |_| Err("asked for recipients".to_owned()),
+            |_| Err("asked for CC recipients".to_owned()),
+            |_| Err("asked for BCC recipients".to_owned()),
+            || "no recipients".to_owned(),
+            |error| error.to_string(),
+            |_| {},
+        )
+    }
+
+    #[test]
+    fn editing_only_sender_keeps_smtp_server() {
+        let sender = EmailAccount::builder()
+            .name("Alice Smith".to_owned())
+            .email(EmailAddress::from_str("alice@gmail.com").unwrap())
+            .build();
+        let edited = edit(
+            encrypted_settings(),
+            EmailSettingsSelector::Sender,
+            sender.clone(),
+        )
+        .unwrap();
+
+        assert_eq!(edited.sender(), &sender);
+        assert_eq!(edited.smtp_server(), &custom_smtp_server());
+    }
+
+    #[test]
+    fn editing_sender_reencrypts_app_password_without_asking_for_it() {
+        let sender = EmailAccount::builder()
+            .name("Alice Smith".to_owned())
+            .email(EmailAddress::from_str("alice@example.org").unwrap())
+            .build();
+        let edited = edit(
+            encrypted_settings(),
+            EmailSettingsSelector::Sender,
+            sender.clone(),
+        )
+        .unwrap();
+
+        assert_eq!(edited.sender(), &sender);
+        let decrypted = edited
+            .decrypt_smtp_app_password(SecretString::from("open sesame"))
+            .unwrap();
+        assert_eq!(
+            decrypted.smtp_app_password().expose_secret(),
+            "app password"
+        );
+    }
+}
@@ -1,7 +1,7 @@
-use crate::EmailAddress;
+use crate::{EmailAddress, Recipient};
 use derive_more::Display;
 use indexmap::IndexSet;
-use inquire::{Confirm, CustomType};
+use inquire::{Confirm, CustomType, Text};
 use log::warn;
 
 use super::{Error, Result, format_help_skippable};
@@ -48,21 +48,48 @@ pub fn ask_for_email_address(
         .map_err(Error::invalid_email_address_for_role(role))
 }
 
+/// Asks for a recipient's email address and optional display name, `None` if skipped.
+pub fn ask_for_recipient_skippable(
+    role: EmailAddressRole,
+    default: Option<&Recipient>,
+) -> Result<Option<Recipient>> {
+    let Some(email) = ask_for_email_address_skippable(role, default.map(|d| d.email()))? else {
+        return Ok(None);
+    };
+
+    let label = format!("{}'s name?", role);
+    let help = format_help_skippable(format!("Display name for {} (optional)", role));
+    let prompt = Text::new(&label).with_help_message(&help);
+    let default_name = default
+        .filter(|d| d.email() == &email)
+        .and_then(|d| d.name().as_deref());
+    let name = if let Some(default_name) = default_name {
+        prompt.with_default(default_name).prompt_skippable()
+    } else {
+        prompt.prompt_skippable()
+    }
+    .map_err(Error::invalid_name_for_email_for_role(role))?
+    .map(|name| name.trim().to_owned())
+    .filter(|name| !name.is_empty());
+
+    Ok(Some(
+        Recipient::builder().maybe_name(name).email(email).build(),
+    ))
+}
+
 pub fn ask_for_many_email_addresses(
     role: EmailAddressRole,
-    defaults: &IndexSet<EmailAddress>,
-) -> Result<IndexSet<EmailAddress>> {
-    let mut emails = IndexSet::new();
-    loop {
-        let Some(email) = ask_for_email_address_skippable(role, defaults.get_index(emails.len()))?
-        else {
-            break;
-        };
-        if emails.contains(&email) {
+    defaults: &IndexSet<Recipient>,
+) -> Result<IndexSet<Recipient>> {
+    let mut recipients = IndexSet::<Recipient>::new();
+    while let Some(recipient) =
+        ask_for_recipient_skippable(role, defaults.get_index(recipients.len()))?
+    {
+        if recipients.iter().any(|r| r.email() == recipient.email()) {
             warn!("Email address already exists, skipping");
             continue;
         }
-        emails.insert(email);
+        recipients.insert(recipient);
         let another = Confirm::new(&format!("Add another {} email address?", role))
             .with_default(true)
             .prompt()
@@ -71,5 +98,5 @@ pub fn ask_for_many_email_addresses(
             break;
         }
     }
-    Ok(emails)
+    Ok(recipients)
 }
@@ -31,6 +31,9 @@ pub enum Error {
 
     #[error("Failed to parse SMTP Server, because: {underlying}")]
     InvalidSmtpServer { underlying: String },
+
+    #[error("Invalid email profile name, because: {underlying}")]
+    InvalidProfileName { underlying: String },
 }
 
 impl Error {
@@ -46,6 +49,12 @@ impl Error {
         }
     }
 
+    pub fn invalid_profile_name(underlying: impl std::fmt::Display) -> Self {
+        Self::InvalidProfileName {
+            underlying: underlying.to_string(),
+        }
+    }
+
     pub fn invalid_email_address_for_role<E: std::fmt::Display>(
         role: impl std::fmt::Display,
     ) -> impl FnOnce(E) -> Self {
@@ -82,6 +82,17 @@ pub fn ask_for_email_encryption_password_with_confirmation(
     )
 }
 
+/// Asks for the encryption password currently protecting the SMTP App Password,
+/// e.g. before changing it. Unlike [`get_email_encryption_password`] this never
+/// reads the password from the environment.
+pub fn ask_for_current_email_encryption_password() -> Result<SecretString> {
+    ask_for_password(
+        false,
+        "Current Encryption Password",
+        "Used to decrypt the SMTP App Password",
+    )
+}
+
 pub fn get_email_encryption_password() -> Result<SecretString> {
     ask_for_email_encryption_password_with_confirmation(false)
 }
@@ -0,0 +1,48 @@
+use inquire::{Select, Text};
+
+use crate::EmailProfiles;
+
+use super::{Error, Result};
+
+/// An option when picking the profile, either an existing one or a new one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, derive_more::Display)]
+enum ProfileOption<'a> {
+    #[display("{_0}")]
+    Existing(&'a str),
+    #[display("New profile…")]
+    New,
+}
+
+/// Asks which profile of `profiles` to edit, or for the name of a new profile.
+pub fn ask_for_profile_name(profiles: &EmailProfiles) -> Result<String> {
+    let options = profiles
+        .names()
+        .map(ProfileOption::Existing)
+        .chain(std::iter::once(ProfileOption::New))
+        .collect::<Vec<_>>();
+    let starting_cursor = profiles
+        .names()
+        .position(|name| name == profiles.default_profile_name())
+        .unwrap_or_default();
+    let selected = Select::new("Email profile?", options)
+        .with_help_message("The profile, e.g. sending mailbox, to edit")
+        .with_starting_cursor(starting_cursor)
+        .prompt()
+        .map_err(Error::invalid_profile_name)?;
+    if let ProfileOption::Existing(name) = selected {
+        return Ok(name.to_owned());
+    }
+    let name = Text::new("Profile name?")
+        .with_help_message("A name for the new profile, e.g. \"billing\"")
+        .prompt()
+        .map_err(Error::invalid_profile_name)?;
+    if name.trim().is_empty() {
+        return Err(Error::invalid_profile_name("name cannot be empty"));
+    }
+    if profiles.contains(&name) {
+        return Err(Error::invalid_profile_name(format!(
+            "a profile named '{name}' already exists"
+        )));
+    }
+    Ok(name)
+}
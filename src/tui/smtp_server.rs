@@ -1,13 +1,122 @@
-use inquire::CustomType;
+use inquire::{CustomType, Select, Text};
 
-use crate::SmtpServer;
+use crate::{SmtpPreset, SmtpServer, TlsMode};
 
 use super::{Error, Result};
 
+/// An option when picking the SMTP server, either a preset or custom settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, derive_more::Display)]
+enum SmtpServerOption {
+    #[display("{} ({}:{})", _0.name(), _0.host(), _0.port())]
+    Preset(&'static SmtpPreset),
+    #[display("Custom…")]
+    Custom,
+}
+
 pub fn ask_for_smtp_server(default: &SmtpServer) -> Result<SmtpServer> {
-    CustomType::<SmtpServer>::new("SMTP server?")
-        .with_help_message("The SMTP server to use for sending emails")
-        .with_default(default.clone())
+    let options = SmtpPreset::ALL
+        .iter()
+        .map(SmtpServerOption::Preset)
+        .chain(std::iter::once(SmtpServerOption::Custom))
+        .collect::<Vec<_>>();
+    let default_option = SmtpPreset::matching(default)
+        .map(SmtpServerOption::Preset)
+        .unwrap_or(SmtpServerOption::Custom);
+    let starting_cursor = options
+        .iter()
+        .position(|option| *option == default_option)
+        .unwrap_or_default();
+    let selected = Select::new("SMTP server?", options)
+        .with_help_message("The provider to send emails with, or custom server settings")
+        .with_starting_cursor(starting_cursor)
         .prompt()
-        .map_err(Error::invalid_smtp_server)
+        .map_err(Error::invalid_smtp_server)?;
+
+    match selected {
+        SmtpServerOption::Preset(preset) => Ok(preset_smtp_server(preset, default)),
+        SmtpServerOption::Custom => ask_for_custom_smtp_server(default),
+    }
+}
+
+/// The SMTP server of `preset`, keeping the timeout of `default` and, if
+/// `default` is a server of the same preset, its message size limit. A limit
+/// set for another provider does not apply, the one of the preset does.
+pub(super) fn preset_smtp_server(preset: &SmtpPreset, default: &SmtpServer) -> SmtpServer {
+    let max_message_size = if SmtpPreset::matching(default) == Some(preset) {
+        default.max_message_size()
+    } else {
+        None
+    };
+    SmtpServer::builder()
+        .host(preset.host())
+        .port(preset.port())
+        .tls(preset.tls())
+        .maybe_timeout(default.timeout())
+        .maybe_max_message_size(max_message_size)
+        .build()
+}
+
+fn ask_for_custom_smtp_server(default: &SmtpServer) -> Result<SmtpServer> {
+    let host = Text::new("SMTP server host?")
+        .with_help_message("The SMTP server host to use for sending emails")
+        .with_default(default.host())
+        .prompt()
+        .map_err(Error::invalid_smtp_server)?;
+
+    let starting_cursor = TlsMode::ALL
+        .iter()
+        .position(|mode| *mode == default.tls())
+        .unwrap_or_default();
+    let tls = Select::new("SMTP TLS mode?", TlsMode::ALL.to_vec())
+        .with_help_message("How the connection to the SMTP server is secured")
+        .with_starting_cursor(starting_cursor)
+        .prompt()
+        .map_err(Error::invalid_smtp_server)?;
+
+    let default_port = if tls == default.tls() {
+        default.port()
+    } else {
+        tls.default_port()
+    };
+    let port = CustomType::<u16>::new("SMTP server port?")
+        .with_help_message("The port of the SMTP server")
+        .with_default(default_port)
+        .prompt()
+        .map_err(Error::invalid_smtp_server)?;
+
+    Ok(SmtpServer::builder()
+        .host(host)
+        .port(port)
+        .tls(tls)
+        .maybe_timeout(default.timeout())
+        .maybe_max_message_size(default.max_message_size())
+        .build())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn preset_smtp_server_drops_size_limit_of_other_provider() {
+        let outlook = SmtpPreset::by_id("outlook").unwrap();
+        let gmail = SmtpPreset::by_id("gmail").unwrap();
+        let default = SmtpServer::builder()
+            .host(outlook.host())
+            .port(outlook.port())
+            .tls(outlook.tls())
+            .timeout(Duration::from_secs(30))
+            .max_message_size(5_000_000)
+            .build();
+
+        assert_eq!(preset_smtp_server(outlook, &default), default);
+        let switched = preset_smtp_server(gmail, &default);
+        assert_eq!(switched.timeout(), Some(Duration::from_secs(30)));
+        assert_eq!(switched.max_message_size(), None);
+        assert_eq!(
+            switched.effective_max_message_size(),
+            gmail.max_message_size()
+        );
+    }
 }
@@ -1,26 +1,119 @@
-use inquire::CustomType;
+use inquire::{
+    CustomType, CustomUserError,
+    validator::{ErrorMessage, Validation},
+};
 
 use crate::{Template, TemplatePart};
 
-use super::{Error, Result};
+use super::{Error, Result, format_help_skippable};
+
+/// Rejects template parts which cannot be parsed or, if `available` is set,
+/// which use variables not in `available`.
+fn validate_template_part(
+    available: Option<Vec<String>>,
+) -> impl Fn(&TemplatePart) -> std::result::Result<Validation, CustomUserError> + Clone {
+    move |part| {
+        let placeholders = match part.placeholders() {
+            Ok(placeholders) => placeholders,
+            Err(error) => return Ok(Validation::Invalid(ErrorMessage::Custom(error.to_string()))),
+        };
+        let Some(available) = &available else {
+            return Ok(Validation::Valid);
+        };
+        Ok(placeholders
+            .iter()
+            .find(|placeholder| !available.iter().any(|name| name == placeholder.variable()))
+            .map_or(Validation::Valid, |unknown| {
+                Validation::Invalid(ErrorMessage::Custom(format!(
+                    "Unknown variable {} at line {}, column {}",
+                    unknown.variable(),
+                    unknown.line(),
+                    unknown.column()
+                )))
+            }))
+    }
+}
+
+fn help_with_variables(tutorial: &str, available: Option<&[String]>, default: &Template) -> String {
+    let (label, variables) = match available {
+        Some(available) => ("Available variables", available.to_vec()),
+        None => (
+            "Variables in use",
+            default
+                .variables()
+                .map(|variables| variables.into_iter().collect())
+                .unwrap_or_default(),
+        ),
+    };
+    if variables.is_empty() {
+        return tutorial.to_owned();
+    }
+    let variables = variables
+        .iter()
+        .map(|name| format!("<{name}>"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("{tutorial} ({label}: {variables})")
+}
 
 fn ask_for_template_part(
     part: &str,
     default: &TemplatePart,
-    tutorial: &str,
+    help: &str,
+    available: Option<&[String]>,
 ) -> Result<TemplatePart> {
     CustomType::<TemplatePart>::new(&format!("Email template for {}", part))
-        .with_help_message(tutorial)
+        .with_help_message(help)
         .with_default(default.clone())
+        .with_validator(validate_template_part(available.map(<[_]>::to_vec)))
         .prompt()
         .map_err(Error::email_atom_template_error)
 }
 
+fn ask_for_template_part_skippable(
+    part: &str,
+    default: Option<&TemplatePart>,
+    help: &str,
+    available: Option<&[String]>,
+) -> Result<Option<TemplatePart>> {
+    let label = format!("Email template for {}", part);
+    let help = format_help_skippable(help.to_owned());
+    let prompt = CustomType::<TemplatePart>::new(&label)
+        .with_help_message(&help)
+        .with_validator(validate_template_part(available.map(<[_]>::to_vec)));
+
+    let result = if let Some(default) = default {
+        prompt.with_default(default.clone()).prompt_skippable()
+    } else {
+        prompt.prompt_skippable()
+    };
+
+    result.map_err(Error::email_atom_template_error)
+}
+
 pub fn ask_for_template(default: &Template, tutorial: &str) -> Result<Template> {
-    let subject = ask_for_template_part("subject", default.subject_format(), tutorial)?;
-    let body = ask_for_template_part("body", default.body_format(), tutorial)?;
+    ask_for_template_with_variables(default, tutorial, None)
+}
+
+/// Like [`ask_for_template`] but only accepts placeholders for the variables
+/// in `available`, which are shown to the user, if set.
+pub fn ask_for_template_with_variables(
+    default: &Template,
+    tutorial: &str,
+    available: Option<&[String]>,
+) -> Result<Template> {
+    let help = help_with_variables(tutorial, available, default);
+    let subject = ask_for_template_part("subject", default.subject_format(), &help, available)?;
+    let body = ask_for_template_part("body", default.body_format(), &help, available)?;
+    let html_body = ask_for_template_part_skippable(
+        "HTML body",
+        default.html_body_format().as_ref(),
+        &help,
+        available,
+    )?;
     Ok(Template::builder()
         .subject_format(subject)
         .body_format(body)
+        .maybe_html_body_format(html_body)
         .build())
 }